@@ -7,7 +7,10 @@ use cw721::{
     AllNftInfoResponse, ApprovedForAllResponse, ContractInfoResponse, NftInfoResponse,
     NumTokensResponse, OwnerOfResponse, TokensResponse,
 };
-use cw721_metadata_onchain::{ExecuteMsg, Extension, InstantiateMsg, MinterResponse, QueryMsg};
+use cw721_metadata_onchain::{
+    CheckRoyaltiesResponse, ExecuteMsg, Extension, InstantiateMsg, MetadataFrozenResponse,
+    MinterResponse, QueryMsg, RoyaltiesInfoResponse, TransferHookMsg, TransferHooksResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -34,4 +37,9 @@ fn main() {
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(TransferHooksResponse), &out_dir);
+    export_schema(&schema_for!(TransferHookMsg), &out_dir);
+    export_schema(&schema_for!(RoyaltiesInfoResponse), &out_dir);
+    export_schema(&schema_for!(CheckRoyaltiesResponse), &out_dir);
+    export_schema(&schema_for!(MetadataFrozenResponse), &out_dir);
 }
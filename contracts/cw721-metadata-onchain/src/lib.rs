@@ -1,8 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use cosmwasm_std::Empty;
-pub use cw721_base::{ContractError, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128, WasmMsg,
+};
+use cw721::Expiration;
+pub use cw721_base::{InstantiateMsg, MintMsg, MinterResponse};
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Trait {
@@ -23,19 +29,631 @@ pub struct Metadata {
     pub background_color: Option<String>,
     pub animation_url: Option<String>,
     pub youtube_url: Option<String>,
+    /// Whole percentage of every sale paid to `royalty_payment_address`,
+    /// overrides the contract default royalty
+    pub royalty_percentage: Option<u64>,
+    pub royalty_payment_address: Option<String>,
 }
 
 pub type Extension = Option<Metadata>;
 
 pub type Cw721MetadataContract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty>;
-pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
+
+/// Contracts called after every `TransferNft` and `SendNft`, managed by the minter
+pub const TRANSFER_HOOKS: Item<Vec<Addr>> = Item::new("transfer_hooks");
+
+/// Royalty of tokens minted without their own royalty, managed by the minter
+pub const DEFAULT_ROYALTY: Item<Royalty> = Item::new("default_royalty");
+
+/// Once set, the metadata of all tokens, including those minted later, can't be updated anymore
+pub const METADATA_FROZEN: Item<bool> = Item::new("metadata_frozen");
+/// Tokens whose metadata can't be updated anymore
+pub const FROZEN_TOKENS: Map<&str, bool> = Map::new("frozen_tokens");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Royalty {
+    pub payment_address: Addr,
+    pub percentage: u64,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Base(#[from] cw721_base::ContractError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Transfer hook {contract} is already registered")]
+    HookAlreadyRegistered { contract: String },
+
+    #[error("Transfer hook {contract} is not registered")]
+    HookNotRegistered { contract: String },
+
+    #[error("Royalty percentage {percentage} exceeds 100")]
+    InvalidRoyaltyPercentage { percentage: u64 },
+
+    #[error("Royalty percentage and payment address must be set together")]
+    IncompleteRoyalty {},
+
+    #[error("Metadata of token {token_id} is frozen")]
+    MetadataFrozen { token_id: String },
+}
+
+/// Same as the cw721-base `ExecuteMsg`, extended with the transfer hooks management
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Transfer is a base message to move a token to another account without triggering actions
+    TransferNft { recipient: String, token_id: String },
+    /// Send is a base message to transfer a token to a contract and trigger an action
+    /// on the receiving contract.
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    /// Allows operator to transfer / send the token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    /// Remove previously granted Approval
+    Revoke { spender: String, token_id: String },
+    /// Allows operator to transfer / send any token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    /// Remove previously granted ApproveAll permission
+    RevokeAll { operator: String },
+
+    /// Mint a new NFT, can only be called by the contract minter
+    Mint(MintMsg<Extension>),
+
+    /// Registers a contract to be called with `TransferHookMsg` after every transfer,
+    /// can only be called by the contract minter. A failing hook fails the transfer.
+    AddTransferHook { contract: String },
+    /// Unregisters a transfer hook, can only be called by the contract minter
+    RemoveTransferHook { contract: String },
+
+    /// Sets the royalty of tokens minted without their own royalty,
+    /// can only be called by the contract minter
+    SetDefaultRoyalty {
+        royalty_percentage: u64,
+        royalty_payment_address: String,
+    },
+    /// Removes the default royalty, can only be called by the contract minter
+    RemoveDefaultRoyalty {},
+
+    /// Replaces the metadata of a token, e.g. to reveal the art,
+    /// can only be called by the contract minter
+    UpdateMetadata {
+        token_id: String,
+        extension: Extension,
+    },
+    /// Permanently freezes the metadata of the token, or of all tokens when unset,
+    /// can only be called by the contract minter
+    FreezeMetadata { token_id: Option<String> },
+}
+
+/// Same as the cw721-base `QueryMsg`, extended with the transfer hooks
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Return the owner of the given token, error if token does not exist
+    /// Return type: OwnerOfResponse
+    OwnerOf {
+        token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+    /// List all operators that can access all of the owner's tokens
+    /// Return type: `ApprovedForAllResponse`
+    ApprovedForAll {
+        owner: String,
+        /// unset or false will filter out expired items, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Total number of tokens issued
+    NumTokens {},
+
+    /// Returns top-level metadata about the contract: `ContractInfoResponse`
+    ContractInfo {},
+    /// Returns metadata about one particular token: `NftInfoResponse`
+    NftInfo {
+        token_id: String,
+    },
+    /// Returns the result of both `NftInfo` and `OwnerOf` as one query: `AllNftInfo`
+    AllNftInfo {
+        token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+
+    /// Returns all tokens owned by the given address, [] if unset.
+    /// Return type: TokensResponse.
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Requires pagination. Lists all token_ids controlled by the contract.
+    /// Return type: TokensResponse.
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    // Return the minter
+    Minter {},
+
+    /// Returns the registered transfer hooks: `TransferHooksResponse`
+    TransferHooks {},
+
+    /// Returns the royalty owed for selling the token at the given price,
+    /// following EIP-2981: `RoyaltiesInfoResponse`
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// Tells marketplaces that this contract implements royalties: `CheckRoyaltiesResponse`
+    CheckRoyalties {},
+
+    /// Returns whether the metadata of the token, or of all tokens when unset,
+    /// is frozen: `MetadataFrozenResponse`
+    MetadataFrozen {
+        token_id: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferHooksResponse {
+    pub hooks: Vec<String>,
+}
+
+/// Empty address and zero amount when the token carries no royalty
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataFrozenResponse {
+    pub frozen: bool,
+}
+
+/// Sent to every registered transfer hook after the token changed its owner
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferHookMsg {
+    pub sender: String,
+    pub previous_owner: String,
+    pub recipient: String,
+    pub token_id: String,
+}
+
+impl TransferHookMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = TransferHookExecuteMsg::TransferHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+enum TransferHookExecuteMsg {
+    TransferHook(TransferHookMsg),
+}
+
+type BaseExecuteMsg = cw721_base::ExecuteMsg<Extension>;
+type BaseQueryMsg = cw721_base::QueryMsg;
+
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer(
+            deps,
+            env,
+            info,
+            token_id.clone(),
+            BaseExecuteMsg::TransferNft {
+                recipient,
+                token_id,
+            },
+        ),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => execute_transfer(
+            deps,
+            env,
+            info,
+            token_id.clone(),
+            BaseExecuteMsg::SendNft {
+                contract,
+                token_id,
+                msg,
+            },
+        ),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => execute_base(
+            deps,
+            env,
+            info,
+            BaseExecuteMsg::Approve {
+                spender,
+                token_id,
+                expires,
+            },
+        ),
+        ExecuteMsg::Revoke { spender, token_id } => execute_base(
+            deps,
+            env,
+            info,
+            BaseExecuteMsg::Revoke { spender, token_id },
+        ),
+        ExecuteMsg::ApproveAll { operator, expires } => execute_base(
+            deps,
+            env,
+            info,
+            BaseExecuteMsg::ApproveAll { operator, expires },
+        ),
+        ExecuteMsg::RevokeAll { operator } => {
+            execute_base(deps, env, info, BaseExecuteMsg::RevokeAll { operator })
+        }
+        ExecuteMsg::Mint(msg) => {
+            if let Some(metadata) = &msg.extension {
+                validate_royalty(deps.as_ref(), metadata)?;
+            }
+            execute_base(deps, env, info, BaseExecuteMsg::Mint(msg))
+        }
+        ExecuteMsg::AddTransferHook { contract } => add_transfer_hook(deps, info, contract),
+        ExecuteMsg::RemoveTransferHook { contract } => remove_transfer_hook(deps, info, contract),
+        ExecuteMsg::SetDefaultRoyalty {
+            royalty_percentage,
+            royalty_payment_address,
+        } => set_default_royalty(deps, info, royalty_percentage, royalty_payment_address),
+        ExecuteMsg::UpdateMetadata {
+            token_id,
+            extension,
+        } => update_metadata(deps, info, token_id, extension),
+        ExecuteMsg::FreezeMetadata { token_id } => freeze_metadata(deps, info, token_id),
+        ExecuteMsg::RemoveDefaultRoyalty {} => {
+            assert_minter(deps.as_ref(), &info.sender)?;
+            DEFAULT_ROYALTY.remove(deps.storage);
+            Ok(Response::new().add_attribute("action", "remove_default_royalty"))
+        }
+    }
+}
+
+fn execute_base(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: BaseExecuteMsg,
+) -> Result<Response, ContractError> {
+    Ok(Cw721MetadataContract::default().execute(deps, env, info, msg)?)
+}
+
+/// Executes the transfer and notifies every registered transfer hook about the new owner
+fn execute_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    msg: BaseExecuteMsg,
+) -> Result<Response, ContractError> {
+    let contract = Cw721MetadataContract::default();
+    let previous_owner = contract.tokens.load(deps.storage, &token_id)?.owner;
+    let res = contract.execute(deps.branch(), env, info.clone(), msg)?;
+    let recipient = contract.tokens.load(deps.storage, &token_id)?.owner;
+
+    let hook_msg = TransferHookMsg {
+        sender: info.sender.to_string(),
+        previous_owner: previous_owner.to_string(),
+        recipient: recipient.to_string(),
+        token_id,
+    };
+    let hooks = TRANSFER_HOOKS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| hook_msg.clone().into_cosmos_msg(hook))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(res.add_messages(hooks))
+}
+
+fn assert_minter(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if Cw721MetadataContract::default().minter.load(deps.storage)? != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn add_transfer_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    let hook = deps.api.addr_validate(&contract)?;
+    let mut hooks = TRANSFER_HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&hook) {
+        return Err(ContractError::HookAlreadyRegistered { contract });
+    }
+    hooks.push(hook);
+    TRANSFER_HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_transfer_hook")
+        .add_attribute("contract", contract))
+}
+
+fn remove_transfer_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    let hook = deps.api.addr_validate(&contract)?;
+    let mut hooks = TRANSFER_HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.contains(&hook) {
+        return Err(ContractError::HookNotRegistered { contract });
+    }
+    hooks.retain(|registered| *registered != hook);
+    TRANSFER_HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_transfer_hook")
+        .add_attribute("contract", contract))
+}
+
+fn assert_royalty_percentage(percentage: u64) -> Result<(), ContractError> {
+    if percentage > 100 {
+        return Err(ContractError::InvalidRoyaltyPercentage { percentage });
+    }
+    Ok(())
+}
+
+/// Token royalties need both the percentage and a valid payment address
+fn validate_royalty(deps: Deps, metadata: &Metadata) -> Result<(), ContractError> {
+    match (
+        metadata.royalty_percentage,
+        &metadata.royalty_payment_address,
+    ) {
+        (Some(percentage), Some(address)) => {
+            assert_royalty_percentage(percentage)?;
+            deps.api.addr_validate(address)?;
+            Ok(())
+        }
+        (None, None) => Ok(()),
+        _ => Err(ContractError::IncompleteRoyalty {}),
+    }
+}
+
+fn set_default_royalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    percentage: u64,
+    payment_address: String,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    assert_royalty_percentage(percentage)?;
+    let royalty = Royalty {
+        payment_address: deps.api.addr_validate(&payment_address)?,
+        percentage,
+    };
+    DEFAULT_ROYALTY.save(deps.storage, &royalty)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_default_royalty")
+        .add_attribute("royalty_percentage", percentage.to_string())
+        .add_attribute("royalty_payment_address", payment_address))
+}
+
+fn is_metadata_frozen(deps: Deps, token_id: Option<&str>) -> StdResult<bool> {
+    if METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Ok(true);
+    }
+    match token_id {
+        Some(token_id) => Ok(FROZEN_TOKENS.has(deps.storage, token_id)),
+        None => Ok(false),
+    }
+}
+
+fn update_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    extension: Extension,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    if is_metadata_frozen(deps.as_ref(), Some(&token_id))? {
+        return Err(ContractError::MetadataFrozen { token_id });
+    }
+    if let Some(metadata) = &extension {
+        validate_royalty(deps.as_ref(), metadata)?;
+    }
+    let contract = Cw721MetadataContract::default();
+    let mut token = contract.tokens.load(deps.storage, &token_id)?;
+    token.extension = extension;
+    contract.tokens.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_metadata")
+        .add_attribute("token_id", token_id))
+}
+
+fn freeze_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_minter(deps.as_ref(), &info.sender)?;
+    let res = Response::new().add_attribute("action", "freeze_metadata");
+    match token_id {
+        Some(token_id) => {
+            // fails for unknown tokens
+            Cw721MetadataContract::default()
+                .tokens
+                .load(deps.storage, &token_id)?;
+            FROZEN_TOKENS.save(deps.storage, &token_id, &true)?;
+            Ok(res.add_attribute("token_id", token_id))
+        }
+        None => {
+            METADATA_FROZEN.save(deps.storage, &true)?;
+            Ok(res.add_attribute("token_id", "all"))
+        }
+    }
+}
+
+/// Royalty of the token itself, falling back to the contract default
+fn query_royalty_info(
+    deps: Deps,
+    token_id: String,
+    sale_price: Uint128,
+) -> StdResult<RoyaltiesInfoResponse> {
+    let token = Cw721MetadataContract::default()
+        .tokens
+        .load(deps.storage, &token_id)?;
+    let token_royalty = token.extension.and_then(|metadata| {
+        match (
+            metadata.royalty_percentage,
+            metadata.royalty_payment_address,
+        ) {
+            (Some(percentage), Some(address)) => Some((address, percentage)),
+            _ => None,
+        }
+    });
+    let royalty = match token_royalty {
+        Some(royalty) => Some(royalty),
+        None => DEFAULT_ROYALTY
+            .may_load(deps.storage)?
+            .map(|royalty| (royalty.payment_address.to_string(), royalty.percentage)),
+    };
+    Ok(match royalty {
+        Some((address, percentage)) => RoyaltiesInfoResponse {
+            address,
+            royalty_amount: sale_price.multiply_ratio(percentage, 100u64),
+        },
+        None => RoyaltiesInfoResponse {
+            address: String::new(),
+            royalty_amount: Uint128::zero(),
+        },
+    })
+}
+
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let base_msg = match msg {
+        QueryMsg::TransferHooks {} => {
+            let hooks = TRANSFER_HOOKS.may_load(deps.storage)?.unwrap_or_default();
+            return to_binary(&TransferHooksResponse {
+                hooks: hooks.into_iter().map(String::from).collect(),
+            });
+        }
+        QueryMsg::RoyaltyInfo {
+            token_id,
+            sale_price,
+        } => return to_binary(&query_royalty_info(deps, token_id, sale_price)?),
+        QueryMsg::MetadataFrozen { token_id } => {
+            return to_binary(&MetadataFrozenResponse {
+                frozen: is_metadata_frozen(deps, token_id.as_deref())?,
+            })
+        }
+        QueryMsg::CheckRoyalties {} => {
+            return to_binary(&CheckRoyaltiesResponse {
+                royalty_payments: true,
+            })
+        }
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => BaseQueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        },
+        QueryMsg::ApprovedForAll {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        } => BaseQueryMsg::ApprovedForAll {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        },
+        QueryMsg::NumTokens {} => BaseQueryMsg::NumTokens {},
+        QueryMsg::ContractInfo {} => BaseQueryMsg::ContractInfo {},
+        QueryMsg::NftInfo { token_id } => BaseQueryMsg::NftInfo { token_id },
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => BaseQueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        },
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => BaseQueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        },
+        QueryMsg::AllTokens { start_after, limit } => {
+            BaseQueryMsg::AllTokens { start_after, limit }
+        }
+        QueryMsg::Minter {} => BaseQueryMsg::Minter {},
+    };
+    Cw721MetadataContract::default().query(deps, env, base_msg)
+}
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
     // This is a simple type to let us handle empty extensions
 
@@ -57,12 +675,12 @@ pub mod entry {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        Cw721MetadataContract::default().execute(deps, env, info, msg)
+        super::execute(deps, env, info, msg)
     }
 
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-        Cw721MetadataContract::default().query(deps, env, msg)
+        super::query(deps, env, msg)
     }
 }
 
@@ -70,6 +688,7 @@ pub mod entry {
 mod tests {
     use super::*;
 
+    use cosmwasm_std::from_binary;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cw721::Cw721Query;
 
@@ -102,12 +721,326 @@ mod tests {
             }),
         };
         let exec_msg = ExecuteMsg::Mint(mint_msg.clone());
-        contract
-            .execute(deps.as_mut(), mock_env(), info, exec_msg)
-            .unwrap();
+        execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
 
         let res = contract.nft_info(deps.as_ref(), token_id.into()).unwrap();
         assert_eq!(res.token_uri, mint_msg.token_uri);
         assert_eq!(res.extension, mint_msg.extension);
     }
+
+    #[test]
+    fn notify_transfer_hooks() {
+        let mut deps = mock_dependencies(&[]);
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let mint_msg = ExecuteMsg::Mint(MintMsg {
+            token_id: "Enterprise".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: None,
+        });
+        execute(deps.as_mut(), mock_env(), info.clone(), mint_msg).unwrap();
+
+        // only minter manages the hooks
+        let hook_msg = ExecuteMsg::AddTransferHook {
+            contract: "nfc".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            hook_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), hook_msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), hook_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::HookAlreadyRegistered {
+                contract: "nfc".to_string()
+            }
+        );
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TransferHooks {}).unwrap();
+        let hooks: TransferHooksResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["nfc".to_string()], hooks.hooks);
+
+        // transfer notifies the hook with the previous and new owner
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "jane".to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+        let notification = TransferHookMsg {
+            sender: "john".to_string(),
+            previous_owner: "john".to_string(),
+            recipient: "jane".to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            notification.into_cosmos_msg("nfc").unwrap(),
+            res.messages[0].msg
+        );
+
+        // send delivers the token before notifying the hook
+        let send_msg = ExecuteMsg::SendNft {
+            contract: "market".to_string(),
+            token_id: "Enterprise".to_string(),
+            msg: Binary::default(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("jane", &[]), send_msg).unwrap();
+        assert_eq!(2, res.messages.len());
+        let notification = TransferHookMsg {
+            sender: "jane".to_string(),
+            previous_owner: "jane".to_string(),
+            recipient: "market".to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        assert_eq!(
+            notification.into_cosmos_msg("nfc").unwrap(),
+            res.messages[1].msg
+        );
+
+        // removed hook is not notified anymore
+        let remove_msg = ExecuteMsg::RemoveTransferHook {
+            contract: "nfc".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), remove_msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, remove_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::HookNotRegistered {
+                contract: "nfc".to_string()
+            }
+        );
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: "john".to_string(),
+            token_id: "Enterprise".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("market", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn query_royalties() {
+        let mut deps = mock_dependencies(&[]);
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let mint_msg = |token_id: &str, percentage: Option<u64>, address: Option<&str>| {
+            ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: Some(Metadata {
+                    royalty_percentage: percentage,
+                    royalty_payment_address: address.map(String::from),
+                    ..Metadata::default()
+                }),
+            })
+        };
+        let royalty_info = |deps: Deps, token_id: &str| -> RoyaltiesInfoResponse {
+            let msg = QueryMsg::RoyaltyInfo {
+                token_id: token_id.to_string(),
+                sale_price: Uint128::new(1_000),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // token royalty needs both fields and a sane percentage
+        let msg = mint_msg("Enterprise", Some(10), None);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::IncompleteRoyalty {});
+        let msg = mint_msg("Enterprise", Some(101), Some("artist"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidRoyaltyPercentage { percentage: 101 }
+        );
+        let msg = mint_msg("Enterprise", Some(10), Some("artist"));
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = mint_msg("Voyager", None, None);
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        assert_eq!(
+            RoyaltiesInfoResponse {
+                address: "artist".to_string(),
+                royalty_amount: Uint128::new(100),
+            },
+            royalty_info(deps.as_ref(), "Enterprise")
+        );
+        assert_eq!(
+            RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::zero(),
+            },
+            royalty_info(deps.as_ref(), "Voyager")
+        );
+
+        // only minter sets the default, which applies to tokens without their own royalty
+        let default_msg = ExecuteMsg::SetDefaultRoyalty {
+            royalty_percentage: 5,
+            royalty_payment_address: "studio".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            default_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), default_msg).unwrap();
+        assert_eq!(
+            RoyaltiesInfoResponse {
+                address: "studio".to_string(),
+                royalty_amount: Uint128::new(50),
+            },
+            royalty_info(deps.as_ref(), "Voyager")
+        );
+        assert_eq!(
+            Uint128::new(100),
+            royalty_info(deps.as_ref(), "Enterprise").royalty_amount
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::RemoveDefaultRoyalty {},
+        )
+        .unwrap();
+        assert_eq!(
+            Uint128::zero(),
+            royalty_info(deps.as_ref(), "Voyager").royalty_amount
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CheckRoyalties {}).unwrap();
+        let check: CheckRoyaltiesResponse = from_binary(&res).unwrap();
+        assert!(check.royalty_payments);
+    }
+
+    #[test]
+    fn update_and_freeze_metadata() {
+        let mut deps = mock_dependencies(&[]);
+        let contract = Cw721MetadataContract::default();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: CREATOR.to_string(),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        for token_id in ["Enterprise", "Voyager"] {
+            let mint_msg = ExecuteMsg::Mint(MintMsg {
+                token_id: token_id.to_string(),
+                owner: "john".to_string(),
+                token_uri: None,
+                extension: None,
+            });
+            execute(deps.as_mut(), mock_env(), info.clone(), mint_msg).unwrap();
+        }
+        let update_msg = |token_id: &str, name: &str| ExecuteMsg::UpdateMetadata {
+            token_id: token_id.to_string(),
+            extension: Some(Metadata {
+                name: Some(name.to_string()),
+                ..Metadata::default()
+            }),
+        };
+        let frozen = |deps: Deps, token_id: Option<&str>| -> bool {
+            let msg = QueryMsg::MetadataFrozen {
+                token_id: token_id.map(String::from),
+            };
+            let res: MetadataFrozenResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.frozen
+        };
+
+        // only minter reveals the metadata
+        let msg = update_msg("Enterprise", "Starship USS Enterprise");
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("john", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = contract
+            .nft_info(deps.as_ref(), "Enterprise".to_string())
+            .unwrap();
+        assert_eq!(
+            Some("Starship USS Enterprise".to_string()),
+            res.extension.unwrap().name
+        );
+
+        // frozen token can't be updated anymore, other tokens still can
+        let msg = ExecuteMsg::FreezeMetadata {
+            token_id: Some("Enterprise".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(frozen(deps.as_ref(), Some("Enterprise")));
+        assert!(!frozen(deps.as_ref(), Some("Voyager")));
+        assert!(!frozen(deps.as_ref(), None));
+        let msg = update_msg("Enterprise", "Enterprise");
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MetadataFrozen {
+                token_id: "Enterprise".to_string()
+            }
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            update_msg("Voyager", "Voyager"),
+        )
+        .unwrap();
+
+        // global freeze covers all tokens
+        let msg = ExecuteMsg::FreezeMetadata { token_id: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(frozen(deps.as_ref(), None));
+        assert!(frozen(deps.as_ref(), Some("Voyager")));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            update_msg("Voyager", "USS Voyager"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MetadataFrozen {
+                token_id: "Voyager".to_string()
+            }
+        );
+    }
 }
@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use cw721_nfc::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cw721_nfc::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use cw721_nfc::state::ContractConfig;
 
 fn main() {
@@ -15,5 +15,6 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(ContractConfig), &out_dir);
 }
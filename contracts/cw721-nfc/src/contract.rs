@@ -1,16 +1,20 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, QueryRequest, WasmQuery, Storage, Order, Uint128, Coin, Addr, BankMsg, BlockInfo, Event, Attribute};
+use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use cosmwasm_std::{from_binary, to_binary, Binary, CosmosMsg, WasmMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, QueryRequest, WasmQuery, Storage, Order, Uint128, Coin, Addr, BankMsg, BlockInfo, Event, Attribute, Reply, SubMsg};
 use cosmwasm_std::CosmosMsg::Bank;
-use cw0::{Expiration, maybe_addr};
 use cw2::set_contract_version;
-use cw721_base::msg::QueryMsg::{OwnerOf};
-use cw721::{OwnerOfResponse};
-use cw_storage_plus::{Bound, PrimaryKey, U32Key, U8Key};
+use cw721_base::msg::QueryMsg::{ApprovedForAll, OwnerOf};
+use cw721::{ApprovedForAllResponse, Cw721ExecuteMsg, Cw721ReceiveMsg, OwnerOfResponse};
+use cw_storage_plus::{Bound, Prefix, PrimaryKey, U32Key, U8Key};
+use cw721_metadata_onchain::{ExecuteMsg as CertificateExecuteMsg, Metadata, MintMsg, Trait, TransferHookMsg};
 
 use crate::error::ContractError;
-use crate::msg::{AllPhysicalsResponse, Cw721AddressResponse, ExecuteMsg, InstantiateMsg, Cw721PhysicalInfoResponse, Cw721PhysicalsResponse, QueryMsg, TierInfoResponse, BidsResponse, BiddingInfoResponse};
-use crate::state::{ContractConfig, CONTRACT_CONFIG, Cw721PhysicalInfo, PHYSICALS_COUNT, physicals, TIERS, TierInfo, BIDS, BidInfo, load_tier_info, BiddingInfo, BIDDING_INFO};
+use crate::migrations::{load_stored_version, parse_version, run_migrations};
+use crate::msg::{AllPhysicalsResponse, Cw721AddressResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, Cw721PhysicalInfoResponse, Cw721PhysicalsResponse, QueryMsg, TierInfoResponse, BidsResponse, BidSlot, BidSlotsResponse, BiddingInfoResponse, PrintOrderItem, Cw721PhysicalsInfoResponse, AllPhysicalsInfoResponse, OrderBy, PhysicalsByOwnerResponse, PhysicalsByStatusResponse, StatsResponse, ConfigResponse, RolesResponse, RoleMembers, CertificateResponse, Cw721HookMsg, EscrowedTokenResponse, TokenClaimsResponse, PhysicalOwnershipHistoryResponse};
+use crate::state::{ContractConfig, CONTRACT_CONFIG, Cw721PhysicalInfo, physicals, next_physical_id, TIERS, TierInfo, bids, BidInfo, BidUniqueness, load_tier_info, BiddingInfo, BiddingMode, BIDDING_INFO, ESCROWED_TOKENS, PhysicalStatus, ClaimPolicy, TokenClaim, TOKEN_CLAIMS, OwnershipChange, PHYSICAL_OWNERS_HISTORY, PENDING_OWNER, ROLES, ROLES_LIST, Role, PausableOperation, load_role_members, load_stats, STATS, ORDERERS, Certificate, CERTIFICATES, RUNNER_UP_BIDS, load_runner_up_bids};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-nfc";
@@ -18,6 +22,7 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
+const MAX_SCAN_LIMIT: usize = 300;
 
 const UUSD_DENOM: &str = "uusd";
 
@@ -30,8 +35,11 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     let contract_info = ContractConfig {
         owner: info.sender.clone(),
-        cw721: msg.cw721,
-        paused: false
+        cw721: deps.api.addr_validate(&msg.cw721)?,
+        paused: vec![],
+        escrow_orders: msg.escrow_orders.unwrap_or(false),
+        claim_policy: msg.claim_policy.unwrap_or_default(),
+        certificate: msg.certificate.map(|certificate| deps.api.addr_validate(&certificate)).transpose()?
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     CONTRACT_CONFIG.save(deps.storage, &contract_info)?;
@@ -45,12 +53,19 @@ pub fn instantiate(
     }
 
     // Initialize Bidding info
+    let mode = msg.bidding_mode.unwrap_or_default();
+    let start = mode.now(&_env.block);
+    let end = start.checked_add(msg.bidding_duration).ok_or(ContractError::InvalidBiddingSchedule {})?;
     BIDDING_INFO.save(deps.storage, &BiddingInfo{
         bids_limit: msg.bids_limit,
         duration: msg.bidding_duration,
         pause_duration: msg.bidding_pause,
-        start: _env.block.height,
-        expires: Expiration::AtHeight(_env.block.height + msg.bidding_duration)
+        start,
+        expires: mode.expiration(end),
+        mode,
+        promote_runner_ups: false,
+        bid_uniqueness: BidUniqueness::default(),
+        next_bids_limit: None
     })?;
 
     Ok(Response::new()
@@ -59,6 +74,63 @@ pub fn instantiate(
         .add_attribute("cw721", contract_info.cw721.as_str()))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = load_stored_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContractName { contract: stored.contract });
+    }
+    if parse_version(&stored.version)? > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::MigrationDowngrade { from: stored.version, to: CONTRACT_VERSION.to_string() });
+    }
+
+    let last_id = run_migrations(deps.storage, &stored.version)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("physicals_count", last_id.to_string()))
+}
+
+/// ## Description
+/// Handles the certificate mint, the reply id is the id of the certified physical item.
+/// A failed mint doesn't revert the order, it is reported by a `certificate_failed` event
+/// and leaves the physical item without a certificate.
+/// Returns [`Ok`] if the reply was handled, otherwise returns [`ContractError`]
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let unknown_reply = || ContractError::UnknownReplyId { id: msg.id };
+    let physical_id = u32::try_from(msg.id).map_err(|_| unknown_reply())?;
+    let physical = physicals()
+        .may_load(deps.storage, &U32Key::from(physical_id).joined_key())?
+        .ok_or_else(unknown_reply)?;
+    let contract = CONTRACT_CONFIG.load(deps.storage)?.certificate.ok_or_else(unknown_reply)?;
+    if let Err(error) = msg.result.into_result() {
+        return Ok(Response::new()
+            .add_attribute("action", "mint_certificate")
+            .add_event(Event::new("certificate_failed")
+                .add_attribute("physical_id", physical_id.to_string())
+                .add_attribute("token_id", physical.token_id)
+                .add_attribute("owner", physical.owner)
+                .add_attribute("certificate_contract", contract)
+                .add_attribute("error", error)));
+    }
+
+    let certificate = Certificate { contract, token_id: physical_id.to_string() };
+    CERTIFICATES.save(deps.storage, U32Key::from(physical_id), &certificate)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "mint_certificate")
+        .add_event(Event::new("certificate_minted")
+            .add_attribute("physical_id", physical_id.to_string())
+            .add_attribute("token_id", physical.token_id)
+            .add_attribute("owner", physical.owner)
+            .add_attribute("certificate_contract", certificate.contract)
+            .add_attribute("certificate_token_id", certificate.token_id)))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -67,64 +139,261 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::OrderCw721Print { token_id, tier} => {
-            assert_not_paused(deps.storage)?;
-            assert_ust(info.funds.clone())?;
-            order_cw721_print(deps, info, token_id, tier)
+        ExecuteMsg::OrderCw721Print { token_id, tier, recipient} => {
+            order_cw721_print(deps, &_env.block, info, token_id, tier, recipient)
+        },
+        ExecuteMsg::OrderCw721Prints { items } => {
+            order_cw721_prints(deps, &_env.block, info, items)
         },
-        ExecuteMsg::Bid721Masterpiece { token_id} => {
-            assert_not_paused(deps.storage)?;
+        ExecuteMsg::Bid721Masterpiece { token_id, recipient} => {
+            assert_not_paused(deps.storage, PausableOperation::Bidding)?;
             assert_ust(info.funds.clone())?;
-            place_bid(deps, &_env.block, info, token_id)
+            place_bid(deps, &_env.block, info, token_id, recipient)
         },
         ExecuteMsg::ResolveBids {} => {
-            assert_not_paused(deps.storage)?;
-            resolve_bids(deps.storage, &_env.block)
+            assert_not_paused(deps.storage, PausableOperation::Resolving)?;
+            resolve_bids(deps, &_env.block)
+        },
+        ExecuteMsg::ReceiveNft(msg) => {
+            receive_nft(deps, info, msg)
+        },
+        ExecuteMsg::TransferHook(msg) => {
+            handle_transfer_hook(deps, _env, info, msg)
+        },
+        ExecuteMsg::WithdrawNft { token_id } => {
+            assert_not_paused(deps.storage, PausableOperation::Withdrawals)?;
+            withdraw_nft(deps, info, token_id)
         },
-        ExecuteMsg::UpdateConfig { owner, paused} => {
+        ExecuteMsg::UpdatePhysicalStatus { physical_id, status } => {
+            assert_role(deps.storage, &info.sender, Role::Operator)?;
+            update_physical_status(deps, &_env.block, physical_id, status)
+        },
+        ExecuteMsg::SyncPhysicalOwner { physical_id } => {
+            sync_physical_owner(deps, &_env.block, physical_id)
+        },
+        ExecuteMsg::TransferPhysical { physical_id, recipient } => {
+            transfer_physical(deps, &_env.block, info, physical_id, recipient)
+        },
+        ExecuteMsg::ProposeNewOwner { owner } => {
             assert_owner(deps.storage, info.clone().sender)?;
-            update_config(deps, owner, paused)
+            propose_new_owner(deps, owner)
         }
-        ExecuteMsg::UpdateTierInfo { tier, max_physical_limit, cost} => {
+        ExecuteMsg::DropOwnershipProposal {} => {
+            assert_owner(deps.storage, info.clone().sender)?;
+            PENDING_OWNER.remove(deps.storage);
+            Ok(Response::new().add_attribute("action", "drop_ownership_proposal"))
+        }
+        ExecuteMsg::AcceptOwnership {} => {
+            accept_ownership(deps, info)
+        }
+        ExecuteMsg::AddRole { role, address } => {
             assert_owner(deps.storage, info.clone().sender)?;
+            add_role(deps, role, address)
+        }
+        ExecuteMsg::RemoveRole { role, address } => {
+            assert_owner(deps.storage, info.clone().sender)?;
+            remove_role(deps, role, address)
+        }
+        ExecuteMsg::WithdrawFunds { amount, recipient } => {
+            assert_role(deps.storage, &info.sender, Role::Treasurer)?;
+            assert_not_paused(deps.storage, PausableOperation::Withdrawals)?;
+            withdraw_funds(deps, _env, info, amount, recipient)
+        }
+        ExecuteMsg::ScheduleBidding { start, mode, duration, pause_duration } => {
+            assert_role(deps.storage, &info.sender, Role::Admin)?;
+            schedule_bidding(deps.storage, &_env.block, start, mode, duration, pause_duration)
+        }
+        ExecuteMsg::AbortBidding { next_start } => {
+            assert_role(deps.storage, &info.sender, Role::Admin)?;
+            abort_bidding(deps.storage, &_env.block, next_start)
+        }
+        ExecuteMsg::UpdateConfig {
+            cw721, paused, escrow_orders, claim_policy, certificate,
+            bids_limit, bidding_duration, bidding_pause, promote_runner_ups, bid_uniqueness
+        } => {
+            let updates_admin_params = cw721.is_some() || escrow_orders.is_some() || claim_policy.is_some()
+                || certificate.is_some() || bids_limit.is_some() || bidding_duration.is_some()
+                || bidding_pause.is_some() || promote_runner_ups.is_some() || bid_uniqueness.is_some();
+            // an empty update would pass without any role check
+            if paused.is_none() && !updates_admin_params {
+                return Err(ContractError::EmptyConfigUpdate {});
+            }
+            if paused.is_some() {
+                assert_role(deps.storage, &info.sender, Role::Pauser)?;
+            }
+            if updates_admin_params {
+                assert_role(deps.storage, &info.sender, Role::Admin)?;
+            }
+            let bidding_attributes =
+                update_bidding_params(deps.storage, &_env.block, bids_limit, bidding_duration, bidding_pause, promote_runner_ups, bid_uniqueness)?;
+            Ok(update_config(deps, cw721, paused, escrow_orders, claim_policy, certificate)?
+                .add_attributes(bidding_attributes))
+        }
+        ExecuteMsg::UpdateTierInfo { tier, max_physical_limit, cost} => {
+            assert_role(deps.storage, &info.sender, Role::Admin)?;
             update_tier_info(deps, tier, max_physical_limit, cost)
         }
     }
 }
-fn order_cw721_print(deps: DepsMut, info: MessageInfo, token_id: String, tier: String) -> Result<Response, ContractError> {
+fn order_cw721_print(
+    deps: DepsMut,
+    block: &BlockInfo,
+    info: MessageInfo,
+    token_id: String,
+    tier: String,
+    recipient: Option<String>
+) -> Result<Response, ContractError> {
     // validate tier
-    let tier : u8= tier.parse().unwrap();
-    if tier != 2 && tier != 3 {
-        return Err(ContractError::InvalidTier {})
-    }
-    // check token ownership
-    let owner: OwnerOfResponse = query_cw721_owner(deps.as_ref(), token_id.clone()).unwrap();
-    if owner.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
+    let tier = parse_print_tier(&tier)?;
+    assert_not_paused(deps.storage, PausableOperation::Ordering { tier })?;
+    assert_ust(info.funds.clone())?;
+    // check token ownership or delegation
+    let owner = if CONTRACT_CONFIG.load(deps.storage)?.escrow_orders {
+        assert_escrow_depositor(deps.storage, &info.sender, &token_id, recipient)?
+    } else {
+        let owner_response = query_token_owner(deps.as_ref(), &token_id)?;
+        assert_token_delegate(deps.as_ref(), &info.sender, &owner_response, recipient)?
+    };
     // Only exact amount of UST accepted
     let tier_info = load_tier_info(deps.storage, tier)?;
-    let ust_amount = info.funds.first().unwrap().amount;
+    let ust_amount = sent_ust_amount(&info.funds)?;
     if ust_amount != Uint128::from(tier_info.costs_sum()) {
         return Err(ContractError::InvalidUSTAmount {
             required: tier_info.costs_sum() as u128,
             sent: ust_amount.u128()});
     }
 
-    is_physical_item_available(deps.storage, &info.sender, &token_id, tier)?;
+    is_physical_item_available(deps.storage, &owner, &token_id, tier)?;
 
-    // Save Cw721Physical item and increment physcials counter
-    let cw721_physical_id = physicals_count(deps.storage).unwrap() + 1;
-    physicals().save(deps.storage, &U32Key::from(cw721_physical_id).joined_key(), &Cw721PhysicalInfo {
-        id: cw721_physical_id,
-        token_id: token_id.clone(),
-        owner: info.sender.clone(),
-        tier,
-        status: "PENDING".to_string()
-    })?;
-    increment_physcials(deps.storage)?;
+    let physical_id = save_physical(deps.storage, block, &token_id, &owner, tier, ust_amount)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_submessages(certificate_mint_msg(deps.storage, physical_id)?)
+        .add_attribute("action", "order_cw721_print")
+        .add_event(order_placed_event(physical_id, &token_id, tier, &owner, &info.sender, ust_amount)))
+}
+
+/// ## Description
+/// Orders physical prints for multiple tokens at once.
+/// All items are validated before any is saved and the sent UST must match the sum of all item costs.
+/// Returns [`Ok`] if all items were ordered, otherwise returns [`ContractError`]
+/// wrapped in [`ContractError::PrintOrderItemFailed`] pointing to the offending item.
+fn order_cw721_prints(
+    deps: DepsMut,
+    block: &BlockInfo,
+    info: MessageInfo,
+    items: Vec<PrintOrderItem>
+) -> Result<Response, ContractError> {
+    if items.is_empty() {
+        return Err(ContractError::EmptyPrintOrder {});
+    }
+    // validate tiers and sum up the required amount of UST
+    let mut tiers: Vec<u8> = vec![];
+    let mut costs: Vec<Uint128> = vec![];
+    let mut required: u128 = 0;
+    for (index, item) in items.iter().enumerate() {
+        let tier = parse_print_tier(&item.tier)
+            .map_err(|err| print_order_item_error(index, item, err))?;
+        assert_not_paused(deps.storage, PausableOperation::Ordering { tier })
+            .map_err(|err| print_order_item_error(index, item, err))?;
+        let cost = load_tier_info(deps.storage, tier)?.costs_sum() as u128;
+        required += cost;
+        tiers.push(tier);
+        costs.push(Uint128::from(cost));
+    }
+    assert_ust(info.funds.clone())?;
+    // Only exact amount of UST accepted
+    let ust_amount = sent_ust_amount(&info.funds)?;
+    if ust_amount != Uint128::from(required) {
+        return Err(ContractError::InvalidUSTAmount { required, sent: ust_amount.u128() });
+    }
+
+    // Validate all items before saving any, same token can be listed multiple times
+    // so its owner is queried only once
+    let escrow_orders = CONTRACT_CONFIG.load(deps.storage)?.escrow_orders;
+    let mut owner_responses: HashMap<String, OwnerOfResponse> = HashMap::new();
+    let mut ordered: HashSet<(String, u8)> = HashSet::new();
+    let mut owners: Vec<Addr> = vec![];
+    for ((index, item), tier) in items.iter().enumerate().zip(tiers.iter()) {
+        let owner = if escrow_orders {
+            assert_escrow_depositor(deps.storage, &info.sender, &item.token_id, item.recipient.clone())
+        } else {
+            if !owner_responses.contains_key(&item.token_id) {
+                let owner_response = query_token_owner(deps.as_ref(), &item.token_id)
+                    .map_err(|err| print_order_item_error(index, item, err))?;
+                owner_responses.insert(item.token_id.clone(), owner_response);
+            }
+            assert_token_delegate(
+                deps.as_ref(),
+                &info.sender,
+                &owner_responses[&item.token_id],
+                item.recipient.clone()
+            )
+        }.map_err(|err| print_order_item_error(index, item, err))?;
+        // Same physical item can not be ordered twice inside the batch either
+        if !ordered.insert((item.token_id.clone(), *tier)) {
+            return Err(print_order_item_error(index, item, ContractError::AlreadyOwned {}));
+        }
+        is_physical_item_available(deps.storage, &owner, &item.token_id, *tier)
+            .map_err(|err| print_order_item_error(index, item, err))?;
+        owners.push(owner);
+    }
+
+    let mut events: Vec<Event> = vec![];
+    let mut certificate_msgs: Vec<SubMsg> = vec![];
+    for (((item, tier), owner), cost) in items.iter().zip(tiers).zip(owners).zip(costs) {
+        let physical_id = save_physical(deps.storage, block, &item.token_id, &owner, tier, cost)?;
+        events.push(order_placed_event(physical_id, &item.token_id, tier, &owner, &info.sender, cost));
+        certificate_msgs.extend(certificate_mint_msg(deps.storage, physical_id)?);
+    }
+
+    Ok(Response::new()
+        .add_submessages(certificate_msgs)
+        .add_attribute("action", "order_cw721_prints")
+        .add_events(events))
+}
+
+fn order_placed_event(
+    physical_id: u32,
+    token_id: &str,
+    tier: u8,
+    owner: &Addr,
+    sender: &Addr,
+    amount: Uint128
+) -> Event {
+    Event::new("order_placed")
+        .add_attribute("physical_id", physical_id.to_string())
+        .add_attribute("token_id", token_id)
+        .add_attribute("tier", tier.to_string())
+        .add_attribute("owner", owner)
+        .add_attribute("sender", sender)
+        .add_attribute("amount", amount)
+}
+
+fn print_order_item_error(index: usize, item: &PrintOrderItem, error: ContractError) -> ContractError {
+    ContractError::PrintOrderItemFailed {
+        index: index as u32,
+        token_id: item.token_id.clone(),
+        error: Box::new(error)
+    }
+}
+
+/// ## Description
+/// Parses the tier of a print order. Only Tier 2 and Tier 3 prints can be ordered,
+/// Tier 1 is reserved for the bidding.
+/// Returns the tier if valid, otherwise returns [`ContractError::InvalidTier`]
+fn parse_print_tier(tier: &str) -> Result<u8, ContractError> {
+    match tier.parse::<u8>() {
+        Ok(tier) => assert_print_tier(tier),
+        _ => Err(ContractError::InvalidTier {})
+    }
+}
+
+fn assert_print_tier(tier: u8) -> Result<u8, ContractError> {
+    match tier {
+        2 | 3 => Ok(tier),
+        _ => Err(ContractError::InvalidTier {})
+    }
 }
 
 fn place_bid(
@@ -132,30 +401,34 @@ fn place_bid(
     block: &BlockInfo,
     info: MessageInfo,
     token_id: String,
+    recipient: Option<String>
 ) -> Result<Response, ContractError> {
     // Check if bidding is on-going/live
     let bidding_info = BIDDING_INFO.load(deps.storage)?;
-    if block.height < bidding_info.start || bidding_info.expires.is_expired(block) {
+    if !bidding_info.is_live(block) {
         return Err(ContractError::BiddingNotAllowed {});
     }
-    // check token ownership
-    let owner: OwnerOfResponse = query_cw721_owner(deps.as_ref(), token_id.clone()).unwrap();
-    if owner.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
+    // check token ownership or delegation
+    let owner_response = query_token_owner(deps.as_ref(), &token_id)?;
+    let owner = assert_token_delegate(deps.as_ref(), &info.sender, &owner_response, recipient)?;
 
-    is_physical_item_available(deps.storage, &info.sender, &token_id, 1)?;
+    is_physical_item_available(deps.storage, &owner, &token_id, 1)?;
+    assert_bid_unique(deps.storage, &bidding_info.bid_uniqueness, &token_id, &info.sender)?;
 
     // fetch all on-going bids
-    let bids : Vec<_> = BIDS
+    let live_bids : Vec<_> = bids()
         .range(deps.storage, None, None, Order::Ascending)
-        .collect::<StdResult<_>>().unwrap();
+        .collect::<StdResult<_>>()?;
 
-    let bids_length = bids.len() as u8;
-    let ust_amount = info.funds.first().unwrap().amount;
+    let bids_length = live_bids.len() as u8;
+    let ust_amount = sent_ust_amount(&info.funds)?;
+    // invalidated bids leave gaps, the first free slot is taken
+    let free_slot = (1..=u8::MAX)
+        .find(|slot| !live_bids.iter().any(|(key, _)| key[0] == *slot))
+        .unwrap_or(u8::MAX);
 
     // Still a free spot available with minimum bid
-    return if bids_length < bidding_info.bids_limit {
+    if bids_length < bidding_info.bids_limit {
         // Amount of UST must be equal or greater than minimum bid
         let tier1_info = load_tier_info(deps.storage, 1)?;
         if ust_amount < Uint128::from(tier1_info.costs_sum()) {
@@ -165,38 +438,62 @@ fn place_bid(
             });
         }
         // Save bid into state
-        BIDS.save(deps.storage, U8Key::from(bids_length + 1), &BidInfo {
+        let bid = BidInfo {
             bid_amount: ust_amount,
             token_id,
-            owner: info.sender.clone()
-        })?;
-        Ok(Response::default())
+            owner,
+            bidder: info.sender.clone()
+        };
+        bids().save(deps.storage, U8Key::from(free_slot), &bid)?;
+        Ok(Response::new()
+            .add_attribute("action", "bid_721_masterpiece")
+            .add_event(bid_placed_event(free_slot, &bid)))
     } else {
         // Check if overbids any of current bids
-        let possible_over_bids = bids
+        let possible_over_bids = live_bids
             .iter()
             .find(|(_, bid)| ust_amount > bid.bid_amount);
 
         match possible_over_bids {
             None => Err(ContractError::LowBidding {}),
             Some((id, old_bid)) => {
-                // Craft message to return UST to bidder
-                let return_ust_msg = Bank(BankMsg::Send {
-                    to_address: old_bid.owner.to_string(),
-                    amount: vec![
-                        Coin {
-                            denom: UUSD_DENOM.to_string(),
-                            amount: old_bid.bid_amount,
-                        },
-                    ],
-                });
+                // Return UST to bidder, unless the bid is held as a runner-up until resolution
+                let mut return_ust_msgs: Vec<CosmosMsg> = vec![];
+                let mut dropped_events: Vec<Event> = vec![];
+                let mut refunded_amount = old_bid.bid_amount;
+                if bidding_info.promote_runner_ups {
+                    refunded_amount = Uint128::zero();
+                    for dropped in hold_runner_up(deps.storage, old_bid, bidding_info.bids_limit)? {
+                        if dropped == *old_bid {
+                            refunded_amount = dropped.bid_amount;
+                        } else {
+                            dropped_events.push(bid_invalidated_event(None, &dropped, "runner_up_dropped"));
+                        }
+                        return_ust_msgs.push(refund_bid_msg(&dropped));
+                    }
+                } else {
+                    return_ust_msgs.push(refund_bid_msg(old_bid));
+                }
                 // Save the new bid
-                BIDS.save(deps.storage, U8Key::from(id[0]), &BidInfo {
+                let bid = BidInfo {
                     bid_amount: ust_amount,
                     token_id,
-                    owner: info.sender.clone()
-                })?;
-                Ok(Response::new().add_message(return_ust_msg))
+                    owner,
+                    bidder: info.sender.clone()
+                };
+                bids().save(deps.storage, U8Key::from(id[0]), &bid)?;
+                Ok(Response::new()
+                    .add_messages(return_ust_msgs)
+                    .add_attribute("action", "bid_721_masterpiece")
+                    .add_event(Event::new("bid_outbid")
+                        .add_attribute("slot", id[0].to_string())
+                        .add_attribute("token_id", &old_bid.token_id)
+                        .add_attribute("owner", &old_bid.owner)
+                        .add_attribute("bidder", &old_bid.bidder)
+                        .add_attribute("refunded_amount", refunded_amount)
+                        .add_attribute("outbid_by", &bid.bidder))
+                    .add_event(bid_placed_event(id[0], &bid))
+                    .add_events(dropped_events))
             }
         }
     }
@@ -207,7 +504,7 @@ fn update_tier_info(deps: DepsMut,
                     max_physical_limit: u8,
                     cost: u64
 ) -> Result<Response, ContractError> {
-    if tier < 1 || tier > 3{
+    if !(1..=3).contains(&tier) {
         return Err(ContractError::InvalidTier {})
     }
     if max_physical_limit == 0 {
@@ -217,27 +514,521 @@ fn update_tier_info(deps: DepsMut,
     let tier_info = TierInfo { max_physical_limit, cost };
     TIERS.save(deps.storage, U8Key::from(tier), &tier_info)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_attribute("action", "update_tier_info")
+        .add_event(Event::new("tier_updated")
+            .add_attribute("tier", tier.to_string())
+            .add_attribute("max_physical_limit", max_physical_limit.to_string())
+            .add_attribute("cost", cost.to_string())))
+}
+
+/// ## Description
+/// Holds the outbid bid as a runner-up. Only the highest runner-ups up to the bids limit are kept,
+/// so that bidding and resolution stay bounded, of equal bids the earlier one is kept.
+/// Returns the runner-ups which didn't make it and need to be refunded.
+fn hold_runner_up(storage: &mut dyn Storage, bid: &BidInfo, bids_limit: u8) -> StdResult<Vec<BidInfo>> {
+    let mut runner_ups = load_runner_up_bids(storage)?;
+    runner_ups.push(bid.clone());
+    runner_ups.sort_by_key(|runner_up| Reverse(runner_up.bid_amount));
+    let dropped = runner_ups.split_off(runner_ups.len().min(bids_limit as usize));
+    RUNNER_UP_BIDS.save(storage, &runner_ups)?;
+    Ok(dropped)
+}
+
+/// ## Description
+/// Verifies that neither the token nor the bidder already has a live bid, as far as the
+/// bid uniqueness policy requires it.
+/// Returns [`ContractError::BidAlreadyExists`] pointing at the conflicting bid.
+fn assert_bid_unique(
+    storage: &dyn Storage,
+    uniqueness: &BidUniqueness,
+    token_id: &str,
+    bidder: &Addr
+) -> Result<(), ContractError> {
+    let mut existing = None;
+    if uniqueness.per_token {
+        existing = bids().idx.token_id
+            .prefix(token_id.to_string())
+            .range(storage, None, None, Order::Ascending)
+            .next();
+    }
+    if existing.is_none() && uniqueness.per_bidder {
+        existing = bids().idx.bidder
+            .prefix(bidder.clone())
+            .range(storage, None, None, Order::Ascending)
+            .next();
+    }
+    match existing {
+        Some(pair) => {
+            let (slot, bid) = pair?;
+            Err(ContractError::BidAlreadyExists {
+                slot: slot[0],
+                token_id: bid.token_id,
+                bidder: bid.bidder.to_string()
+            })
+        }
+        None => Ok(())
+    }
+}
+
+fn bid_invalidated_event(slot: Option<u8>, bid: &BidInfo, reason: &str) -> Event {
+    let event = Event::new("bid_invalidated");
+    let event = match slot {
+        Some(slot) => event.add_attribute("slot", slot.to_string()),
+        None => event
+    };
+    event
+        .add_attribute("token_id", &bid.token_id)
+        .add_attribute("owner", &bid.owner)
+        .add_attribute("bidder", &bid.bidder)
+        .add_attribute("refunded_amount", bid.bid_amount)
+        .add_attribute("reason", reason)
+}
+
+fn refund_bid_msg(bid: &BidInfo) -> CosmosMsg {
+    Bank(BankMsg::Send {
+        to_address: bid.bidder.to_string(),
+        amount: vec![Coin { denom: UUSD_DENOM.to_string(), amount: bid.bid_amount }]
+    })
+}
+
+fn bid_placed_event(slot: u8, bid: &BidInfo) -> Event {
+    Event::new("bid_placed")
+        .add_attribute("slot", slot.to_string())
+        .add_attribute("token_id", &bid.token_id)
+        .add_attribute("owner", &bid.owner)
+        .add_attribute("bidder", &bid.bidder)
+        .add_attribute("amount", bid.bid_amount)
+}
+
+/// ## Description
+/// Handles the NFT sent by the cw721 contract. With escrowed orders enabled, the token is held
+/// by the contract until all of its physical items are delivered or cancelled. The sender of
+/// the token is its depositor, the transfer hook rejects deposits sent by an operator.
+/// Returns [`Ok`] if the token was deposited, otherwise returns [`ContractError`]
+fn receive_nft(deps: DepsMut, info: MessageInfo, msg: Cw721ReceiveMsg) -> Result<Response, ContractError> {
+    let config = CONTRACT_CONFIG.load(deps.storage)?;
+    if info.sender != config.cw721 {
+        return Err(ContractError::Unauthorized {});
+    }
+    match from_binary(&msg.msg)? {
+        Cw721HookMsg::Deposit {} => {
+            if !config.escrow_orders {
+                return Err(ContractError::EscrowDisabled {});
+            }
+            let depositor = deps.api.addr_validate(&msg.sender)?;
+            ESCROWED_TOKENS.save(deps.storage, &msg.token_id, &depositor)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "deposit_nft")
+                .add_attribute("token_id", msg.token_id)
+                .add_attribute("owner", depositor))
+        }
+    }
+}
+
+/// ## Description
+/// Handles the transfer notification of the cw721 contract. Physical items which follow the token
+/// are moved to the recipient and live bids placed for the previous owner are refunded and removed.
+/// Escrow deposits are ignored, escrowed tokens stay owned by their depositor. Deposits sent by
+/// an operator are rejected, which fails the deposit, as the depositor gets the token back.
+/// Notifications of other contracts are ignored, a previously linked cw721 may still have the hook
+/// registered and failing the hook would fail its transfers.
+/// Returns [`Ok`] with the refund messages, otherwise returns [`ContractError`]
+fn handle_transfer_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: TransferHookMsg
+) -> Result<Response, ContractError> {
+    if info.sender != CONTRACT_CONFIG.load(deps.storage)?.cw721 {
+        return Ok(Response::new()
+            .add_attribute("action", "transfer_hook")
+            .add_attribute("token_id", &msg.token_id)
+            .add_attribute("ignored", "unknown_cw721"));
+    }
+    let recipient = deps.api.addr_validate(&msg.recipient)?;
+    let response = Response::new()
+        .add_attribute("action", "transfer_hook")
+        .add_attribute("token_id", &msg.token_id)
+        .add_attribute("recipient", &recipient);
+    if recipient == env.contract.address && msg.sender != msg.previous_owner {
+        return Err(ContractError::DepositNotByOwner {});
+    }
+    if recipient == env.contract.address || ESCROWED_TOKENS.has(deps.storage, &msg.token_id) {
+        return Ok(response.add_attribute("physicals_updated", "0").add_attribute("bids_invalidated", "0"));
+    }
+
+    let followers: Vec<Cw721PhysicalInfo> = physicals()
+        .idx.token_id
+        .prefix(msg.token_id.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, physical)| physical))
+        .filter(|item| item.as_ref().map_or(true, |physical| !physical.detached && physical.owner != recipient))
+        .collect::<StdResult<_>>()?;
+    for mut physical in followers.iter().cloned() {
+        change_physical_owner(deps.storage, &env.block, &mut physical, recipient.clone())?;
+    }
+
+    let invalidated: Vec<(Vec<u8>, BidInfo)> = bids()
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(_, bid)| bid.token_id == msg.token_id && bid.owner != recipient))
+        .collect::<StdResult<_>>()?;
+    let mut refund_msgs: Vec<CosmosMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
+    for (key, bid) in invalidated.iter() {
+        bids().remove(deps.storage, U8Key::from(key[0]))?;
+        refund_msgs.push(refund_bid_msg(bid));
+        events.push(bid_invalidated_event(Some(key[0]), bid, "token_transferred"));
+    }
+    let (invalidated_runner_ups, runner_ups): (Vec<BidInfo>, Vec<BidInfo>) = load_runner_up_bids(deps.storage)?
+        .into_iter()
+        .partition(|bid| bid.token_id == msg.token_id && bid.owner != recipient);
+    if !invalidated_runner_ups.is_empty() {
+        RUNNER_UP_BIDS.save(deps.storage, &runner_ups)?;
+    }
+    for bid in invalidated_runner_ups.iter() {
+        refund_msgs.push(refund_bid_msg(bid));
+        events.push(bid_invalidated_event(None, bid, "token_transferred"));
+    }
+
+    Ok(response
+        .add_messages(refund_msgs)
+        .add_attribute("physicals_updated", followers.len().to_string())
+        .add_attribute("bids_invalidated", (invalidated.len() + invalidated_runner_ups.len()).to_string())
+        .add_events(events))
+}
+
+/// ## Description
+/// Returns the escrowed NFT to the depositor if none of its physical items are in production.
+/// Returns [`Ok`] with the transfer message, otherwise returns [`ContractError`]
+fn withdraw_nft(deps: DepsMut, info: MessageInfo, token_id: String) -> Result<Response, ContractError> {
+    let depositor = ESCROWED_TOKENS.may_load(deps.storage, &token_id)?
+        .ok_or(ContractError::TokenNotEscrowed {})?;
+    if depositor != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if has_open_physicals(deps.storage, &token_id)? {
+        return Err(ContractError::TokenLocked {});
+    }
+    let return_nft_msg = release_escrowed_token(deps.storage, &token_id)?;
+
+    Ok(Response::new()
+        .add_messages(return_nft_msg)
+        .add_attribute("action", "withdraw_nft")
+        .add_attribute("token_id", token_id))
+}
+
+/// ## Description
+/// Updates the status of the physical item. Once the last open physical item of an
+/// escrowed token is delivered or cancelled, the token is returned to its depositor.
+/// Returns [`Ok`] if status was updated, otherwise returns [`ContractError`]
+fn update_physical_status(
+    deps: DepsMut,
+    block: &BlockInfo,
+    physical_id: u32,
+    status: PhysicalStatus
+) -> Result<Response, ContractError> {
+    let key = U32Key::from(physical_id).joined_key();
+    let mut physical = physicals().load(deps.storage, &key)?;
+    if physical.is_closed() {
+        return Err(ContractError::PhysicalItemClosed {});
+    }
+    let mut stats = load_stats(deps.storage)?;
+    stats.change_status(physical.status, status);
+    STATS.save(deps.storage, &stats)?;
+
+    let previous_status = physical.status;
+    physical.status = status;
+    physical.updated_at = block.time;
+    physicals().save(deps.storage, &key, &physical)?;
+    // Cancelled physical item doesn't count as claimed
+    if physical.status == PhysicalStatus::Cancelled {
+        TOKEN_CLAIMS.remove(deps.storage, (&physical.token_id, U32Key::from(physical_id)));
+    }
+
+    let mut return_nft_msg = None;
+    if physical.is_closed() && !has_open_physicals(deps.storage, &physical.token_id)? {
+        return_nft_msg = release_escrowed_token(deps.storage, &physical.token_id)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(return_nft_msg)
+        .add_attribute("action", "update_physical_status")
+        .add_attribute("physical_id", physical_id.to_string())
+        .add_attribute("status", physical.status.to_string())
+        .add_event(Event::new("physical_status_changed")
+            .add_attribute("physical_id", physical_id.to_string())
+            .add_attribute("token_id", &physical.token_id)
+            .add_attribute("owner", &physical.owner)
+            .add_attribute("previous_status", previous_status.to_string())
+            .add_attribute("status", physical.status.to_string())))
+}
+
+/// ## Description
+/// Updates the owner of the physical item to the current owner of its token,
+/// escrowed tokens are owned by their depositor. Can be called by anyone.
+/// Returns [`Ok`] if owner is in sync, otherwise returns [`ContractError`]
+fn sync_physical_owner(deps: DepsMut, block: &BlockInfo, physical_id: u32) -> Result<Response, ContractError> {
+    let mut physical = physicals().load(deps.storage, &U32Key::from(physical_id).joined_key())?;
+    if physical.detached {
+        return Err(ContractError::PhysicalItemDetached {});
+    }
+    let token_owner = match ESCROWED_TOKENS.may_load(deps.storage, &physical.token_id)? {
+        Some(depositor) => depositor,
+        None => Addr::unchecked(query_token_owner(deps.as_ref(), &physical.token_id)?.owner)
+    };
+
+    let changed = physical.owner != token_owner;
+    if changed {
+        change_physical_owner(deps.storage, block, &mut physical, token_owner)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "sync_physical_owner")
+        .add_attribute("physical_id", physical_id.to_string())
+        .add_attribute("owner", physical.owner)
+        .add_attribute("changed", changed.to_string()))
+}
+
+/// ## Description
+/// Transfers the physical item to the recipient, only the current holder can transfer it.
+/// Transferred physical item is detached from its token and can't be synced anymore.
+/// Returns [`Ok`] if physical item was transferred, otherwise returns [`ContractError`]
+fn transfer_physical(
+    deps: DepsMut,
+    block: &BlockInfo,
+    info: MessageInfo,
+    physical_id: u32,
+    recipient: String
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let mut physical = physicals().load(deps.storage, &U32Key::from(physical_id).joined_key())?;
+    if physical.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    physical.detached = true;
+    change_physical_owner(deps.storage, block, &mut physical, recipient)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_physical")
+        .add_attribute("physical_id", physical_id.to_string())
+        .add_attribute("owner", physical.owner))
+}
+
+/// ## Description
+/// Saves the new owner of the physical item and appends the change to its ownership history.
+fn change_physical_owner(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    physical: &mut Cw721PhysicalInfo,
+    owner: Addr
+) -> StdResult<()> {
+    let change = OwnershipChange {
+        previous_owner: physical.owner.clone(),
+        owner: owner.clone(),
+        block_height: block.height
+    };
+    physical.owner = owner;
+    physical.updated_at = block.time;
+    physicals().save(storage, &U32Key::from(physical.id).joined_key(), physical)?;
+    PHYSICAL_OWNERS_HISTORY.update(storage, U32Key::from(physical.id), |history| -> StdResult<_> {
+        let mut history = history.unwrap_or_default();
+        history.push(change);
+        Ok(history)
+    })?;
+    Ok(())
+}
+
+/// ## Description
+/// Checks if the token has physical items which are not delivered or cancelled yet.
+fn has_open_physicals(storage: &dyn Storage, token_id: &str) -> StdResult<bool> {
+    let physical_vec: Vec<Cw721PhysicalInfo> = physicals()
+        .idx.token_id
+        .prefix(token_id.to_string())
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, v)| v))
+        .collect::<StdResult<_>>()?;
+    Ok(physical_vec.iter().any(|physical| !physical.is_closed()))
+}
+
+/// ## Description
+/// Removes the token from escrow and crafts the message returning it to the depositor.
+/// Returns [`None`] if the token is not escrowed.
+fn release_escrowed_token(storage: &mut dyn Storage, token_id: &str) -> StdResult<Option<CosmosMsg>> {
+    let depositor = match ESCROWED_TOKENS.may_load(storage, token_id)? {
+        Some(depositor) => depositor,
+        None => return Ok(None)
+    };
+    ESCROWED_TOKENS.remove(storage, token_id);
+    let config = CONTRACT_CONFIG.load(storage)?;
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.cw721.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: depositor.to_string(),
+            token_id: token_id.to_string()
+        })?,
+        funds: vec![]
+    })))
+}
+
+/// ## Description
+/// Proposes a new owner, replacing any earlier proposal. The current owner keeps the ownership
+/// until the proposed owner accepts it.
+/// Returns [`Ok`] if the owner was proposed, otherwise returns [`ContractError`]
+fn propose_new_owner(deps: DepsMut, owner: String) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(deps.storage, &owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_owner")
+        .add_attribute("pending_owner", owner))
+}
+
+/// ## Description
+/// Transfers the ownership to the sender, who has to be the proposed owner.
+/// Returns [`Ok`] if the ownership was transferred, otherwise returns [`ContractError`]
+fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    if pending_owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut contract_info = CONTRACT_CONFIG.load(deps.storage)?;
+    let previous_owner = contract_info.owner;
+    contract_info.owner = pending_owner;
+    CONTRACT_CONFIG.save(deps.storage, &contract_info)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("previous_owner", previous_owner)
+        .add_attribute("owner", contract_info.owner))
+}
+
+fn add_role(deps: DepsMut, role: Role, address: String) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let mut members = load_role_members(deps.storage, role)?;
+    if !members.contains(&address) {
+        members.push(address.clone());
+        ROLES.save(deps.storage, &role.to_string(), &members)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "add_role")
+        .add_attribute("role", role.to_string())
+        .add_attribute("address", address))
+}
+
+fn remove_role(deps: DepsMut, role: Role, address: String) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let mut members = load_role_members(deps.storage, role)?;
+    members.retain(|member| *member != address);
+    ROLES.save(deps.storage, &role.to_string(), &members)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_role")
+        .add_attribute("role", role.to_string())
+        .add_attribute("address", address))
+}
+
+/// ## Description
+/// Sends collected UST to the recipient, or to the sender when no recipient is given.
+/// Funds of the active bids stay in the contract, so that they can be refunded.
+/// Returns [`Ok`] if the funds were sent, otherwise returns [`ContractError`]
+fn withdraw_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    recipient: Option<String>
+) -> Result<Response, ContractError> {
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender
+    };
+    let balance = deps.querier.query_balance(&env.contract.address, UUSD_DENOM)?.amount;
+    let mut reserved = Uint128::zero();
+    for bid in bids().range(deps.storage, None, None, Order::Ascending) {
+        reserved += bid?.1.bid_amount;
+    }
+    for runner_up in load_runner_up_bids(deps.storage)? {
+        reserved += runner_up.bid_amount;
+    }
+    let available = balance.saturating_sub(reserved);
+    if amount > available {
+        return Err(ContractError::InsufficientFunds { available: available.u128(), requested: amount.u128() });
+    }
+
+    Ok(Response::new()
+        .add_message(Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin { denom: UUSD_DENOM.to_string(), amount }]
+        }))
+        .add_attribute("action", "withdraw_funds")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
 }
 
 fn update_config(deps: DepsMut,
-                 owner: Option<Addr>,
-                 paused: Option<bool>
+                 cw721: Option<String>,
+                 paused: Option<Vec<PausableOperation>>,
+                 escrow_orders: Option<bool>,
+                 claim_policy: Option<ClaimPolicy>,
+                 certificate: Option<String>
 ) -> Result<Response, ContractError> {
     let mut contract_info = CONTRACT_CONFIG.load(deps.storage)?;
 
     let mut attributes: Vec<Attribute> = vec![Attribute::new("action", "update_config")];
 
-    if owner.is_some() {
-        contract_info.owner = owner.unwrap();
+    if let Some(cw721) = cw721 {
+        let cw721 = deps.api.addr_validate(&cw721)?;
+        if cw721 != contract_info.cw721 && ESCROWED_TOKENS.keys(deps.storage, None, None, Order::Ascending).next().is_some() {
+            return Err(ContractError::Cw721InUse {});
+        }
+        contract_info.cw721 = cw721;
         attributes.push(Attribute::new(
-            "owner", contract_info.owner.to_string()
+            "cw721", contract_info.cw721.to_string()
         ))
     }
-    if paused.is_some() {
-        contract_info.paused = paused.unwrap();
+
+    if let Some(paused) = paused {
+        let mut operations: Vec<PausableOperation> = vec![];
+        for operation in paused {
+            if let PausableOperation::Ordering { tier } = operation {
+                assert_print_tier(tier)?;
+            }
+            if !operations.contains(&operation) {
+                operations.push(operation);
+            }
+        }
+        contract_info.paused = operations;
+        attributes.push(Attribute::new(
+            "paused",
+            contract_info.paused.iter().map(|op| op.to_string()).collect::<Vec<String>>().join(",")
+        ))
+    }
+    if let Some(escrow_orders) = escrow_orders {
+        contract_info.escrow_orders = escrow_orders;
+        attributes.push(Attribute::new(
+            "escrow_orders", contract_info.escrow_orders.to_string()
+        ))
+    }
+    if let Some(claim_policy) = claim_policy {
+        contract_info.claim_policy = claim_policy;
         attributes.push(Attribute::new(
-            "paused", contract_info.paused.to_string()
+            "claim_policy", contract_info.claim_policy.to_string()
+        ))
+    }
+    if let Some(certificate) = certificate {
+        contract_info.certificate = if certificate.is_empty() {
+            None
+        } else {
+            Some(deps.api.addr_validate(&certificate)?)
+        };
+        attributes.push(Attribute::new(
+            "certificate", contract_info.certificate.as_ref().map(Addr::to_string).unwrap_or_default()
         ))
     }
 
@@ -246,20 +1037,143 @@ fn update_config(deps: DepsMut,
     Ok(Response::new().add_attributes(attributes))
 }
 
-fn physicals_count(storage: &dyn Storage) -> StdResult<u32> {
-    Ok(PHYSICALS_COUNT.may_load(storage)?.unwrap_or_default())
+/// ## Description
+/// Updates the bidding parameters, the current bidding window keeps its schedule. Bids limit
+/// changed during a live window is staged until the next window, so that live bids keep their slots.
+/// Returns the attributes of the changed parameters in [`Ok`], otherwise returns [`ContractError`]
+fn update_bidding_params(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    bids_limit: Option<u8>,
+    duration: Option<u64>,
+    pause_duration: Option<u64>,
+    promote_runner_ups: Option<bool>,
+    bid_uniqueness: Option<BidUniqueness>
+) -> Result<Vec<Attribute>, ContractError> {
+    let mut bidding_info = BIDDING_INFO.load(storage)?;
+    let mut attributes: Vec<Attribute> = vec![];
+
+    if let Some(bids_limit) = bids_limit {
+        if bids_limit == 0 {
+            return Err(ContractError::BidsLimitIsZero {});
+        }
+        if bidding_info.is_live(block) {
+            bidding_info.next_bids_limit = Some(bids_limit);
+            attributes.push(Attribute::new("next_bids_limit", bids_limit.to_string()));
+        } else {
+            bidding_info.bids_limit = bids_limit;
+            bidding_info.next_bids_limit = None;
+            attributes.push(Attribute::new("bids_limit", bids_limit.to_string()));
+        }
+    }
+    if let Some(duration) = duration {
+        if duration == 0 {
+            return Err(ContractError::BiddingDurationIsZero {});
+        }
+        bidding_info.duration = duration;
+        attributes.push(Attribute::new("bidding_duration", duration.to_string()));
+    }
+    if let Some(pause_duration) = pause_duration {
+        bidding_info.pause_duration = pause_duration;
+        attributes.push(Attribute::new("bidding_pause", pause_duration.to_string()));
+    }
+    if let Some(promote_runner_ups) = promote_runner_ups {
+        bidding_info.promote_runner_ups = promote_runner_ups;
+        attributes.push(Attribute::new("promote_runner_ups", promote_runner_ups.to_string()));
+    }
+    if let Some(bid_uniqueness) = bid_uniqueness {
+        bidding_info.bid_uniqueness = bid_uniqueness;
+        attributes.push(Attribute::new("unique_bids_per_token", bid_uniqueness.per_token.to_string()));
+        attributes.push(Attribute::new("unique_bids_per_bidder", bid_uniqueness.per_bidder.to_string()));
+    }
+
+    BIDDING_INFO.save(storage, &bidding_info)?;
+    Ok(attributes)
+}
+
+/// ## Description
+/// Creates a new pending physical item with a freshly allocated id, records the claim of the token
+/// and updates the stats with the paid amount.
+/// Returns the id of the created physical item.
+fn save_physical(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    token_id: &str,
+    owner: &Addr,
+    tier: u8,
+    paid: Uint128
+) -> StdResult<u32> {
+    let cw721_physical_id = next_physical_id(storage)?;
+    physicals().save(storage, &U32Key::from(cw721_physical_id).joined_key(), &Cw721PhysicalInfo {
+        id: cw721_physical_id,
+        token_id: token_id.to_string(),
+        owner: owner.clone(),
+        tier,
+        status: PhysicalStatus::Pending,
+        detached: false,
+        created_at: block.time,
+        updated_at: block.time
+    })?;
+    TOKEN_CLAIMS.save(storage, (token_id, U32Key::from(cw721_physical_id)), &TokenClaim {
+        physical_id: cw721_physical_id,
+        tier,
+        owner: owner.clone()
+    })?;
+
+    let mut stats = load_stats(storage)?;
+    stats.add_physical(tier, PhysicalStatus::Pending, paid);
+    if !ORDERERS.has(storage, owner) {
+        ORDERERS.save(storage, owner, &true)?;
+        stats.unique_orderers += 1;
+    }
+    STATS.save(storage, &stats)?;
+
+    Ok(cw721_physical_id)
 }
 
-fn increment_physcials(storage: &mut dyn Storage) -> StdResult<u32> {
-    let val = physicals_count(storage)? + 1;
-    PHYSICALS_COUNT.save(storage, &val)?;
-    Ok(val)
+/// ## Description
+/// Builds the mint of the certificate of authenticity for the physical item, its attributes
+/// encode the source token, tier and physical id. The reply with the physical id as its id
+/// records the certificate once the mint succeeds, or reports the failed mint.
+/// Returns [`None`] in [`Ok`] if no certificate contract is configured.
+fn certificate_mint_msg(storage: &dyn Storage, physical_id: u32) -> StdResult<Option<SubMsg>> {
+    let certificate = match CONTRACT_CONFIG.load(storage)?.certificate {
+        Some(certificate) => certificate,
+        None => return Ok(None)
+    };
+    let physical = physicals().load(storage, &U32Key::from(physical_id).joined_key())?;
+    let certificate_trait = |trait_type: &str, value: String| Trait {
+        display_type: None,
+        trait_type: trait_type.to_string(),
+        value
+    };
+    let mint_msg = CertificateExecuteMsg::Mint(MintMsg {
+        token_id: physical_id.to_string(),
+        owner: physical.owner.to_string(),
+        token_uri: None,
+        extension: Some(Metadata {
+            name: Some(format!("Certificate of authenticity #{}", physical_id)),
+            description: Some(format!("Tier {} physical print of token {}", physical.tier, physical.token_id)),
+            attributes: Some(vec![
+                certificate_trait("token_id", physical.token_id.clone()),
+                certificate_trait("tier", physical.tier.to_string()),
+                certificate_trait("physical_id", physical_id.to_string())
+            ]),
+            ..Metadata::default()
+        })
+    });
+    Ok(Some(SubMsg::reply_always(WasmMsg::Execute {
+        contract_addr: certificate.to_string(),
+        msg: to_binary(&mint_msg)?,
+        funds: vec![]
+    }, physical_id as u64)))
 }
 
 /// ## Description
 /// Each tier has a max physical items.
 /// This function checks if there are still any physical items available for a specific Tier.
-/// Additional to that, account can order only 1 item per Tier.
+/// Additional to that, depending on the claim policy either account can order only 1 item per Tier
+/// or token can claim only 1 item per Tier.
 /// Returns [`Ok`] if physical item is still available, , otherwise returns [`ContractError`]
 fn is_physical_item_available(
     storage: &dyn Storage,
@@ -268,31 +1182,39 @@ fn is_physical_item_available(
     tier: u8
 ) -> Result<(), ContractError> {
     let tier_info = load_tier_info(storage, tier)?;
-    // Get physical items by 'token_id' and filter by 'tier'
+    let claims: Vec<TokenClaim> = query_token_claims(storage, token_id.to_string())?.claims;
+    if CONTRACT_CONFIG.load(storage)?.claim_policy == ClaimPolicy::PerToken
+        && claims.iter().any(|claim| claim.tier == tier) {
+        return Err(ContractError::TierAlreadyClaimed {});
+    }
+    // IMO, the following check is pointless, because one can still send token to another account
+    // and order a physical bid from a new account. But this was a requirement from the artsyapes team
+    // Sender can not order same physical item. Claims keep the owner at order time, physical items
+    // follow the token and would block its next holders.
+    if claims.iter().any(|claim| claim.tier == tier && claim.owner == *sender) {
+        return Err(ContractError::AlreadyOwned {});
+    }
+    // Get physical items by 'token_id' and filter by 'tier', cancelled physical items don't count
     let physical_vec : Vec<Cw721PhysicalInfo> = physicals()
         .idx.token_id
         .prefix(token_id.to_string())
         .range(storage, None, None, Order::Ascending)
         .map(|item| item.map(|(_, v)| v))
-        .filter(|item| item.as_ref().unwrap().tier == tier)
-        .collect::<StdResult<_>>().unwrap();
+        .filter(|item| item.as_ref().map_or(true, |physical| {
+            physical.tier == tier && physical.status != PhysicalStatus::Cancelled
+        }))
+        .collect::<StdResult<_>>()?;
 
     // validate  order
     let mut tier_count = 0;
-    for i in physical_vec.iter(){
-        // IMO, the following check is pointless, because one can still send token to another account
-        // and order a physical bid from a new account. But this was a requirement from the artsyapes team
-        // Sender can not order same physical item
-        if i.owner == *sender {
-            return Err(ContractError::AlreadyOwned {});
-        }
+    for _ in physical_vec.iter(){
         tier_count += 1;
         if tier_count == tier_info.max_physical_limit{
             return match tier {
                 1 => Err(ContractError::MaxTier1Items {}),
                 2 => Err(ContractError::MaxTier2Items {}),
                 3 => Err(ContractError::MaxTier3Items {}),
-                _ => panic!("Unexpected invalid tier")
+                _ => Err(ContractError::InvalidTier {})
             }
         }
     }
@@ -301,53 +1223,289 @@ fn is_physical_item_available(
 
 /// ## Description
 /// If the bidding window is expired, the function does the following:
-/// - process all the bids and creates the physicals items.
+/// - re-checks that the owner of every winning bid still holds the token,
+///   bids of transferred tokens are refunded and skipped
+/// - promotes the highest runner-ups still holding their tokens in place of the skipped bids
+///   and refunds the rest of the runner-ups
+/// - process the remaining bids and creates the physicals items, bids of tokens
+///   without an available physical item left are refunded and skipped
 /// - updates the 'BIDDING_INFO' state variable
+///
 /// Returns [`Ok`]
-fn resolve_bids(storage: &mut dyn Storage, block: &BlockInfo) -> Result<Response, ContractError> {
-    let bidding_info = BIDDING_INFO.load(storage)?;
-    if bidding_info.expires.is_expired(&block) {
+fn resolve_bids(deps: DepsMut, block: &BlockInfo) -> Result<Response, ContractError> {
+    let bidding_info = BIDDING_INFO.load(deps.storage)?;
+    if bidding_info.expires.is_expired(block) {
         // fetch all on-going bids
-        let bids : Vec<_> = BIDS
-            .range(storage, None, None, Order::Ascending)
-            .collect::<StdResult<_>>().unwrap();
-        for (key, bid) in bids.iter() {
+        let live_bids : Vec<_> = bids()
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        let mut refund_msgs: Vec<CosmosMsg> = vec![];
+        let mut events: Vec<Event> = vec![];
+        let mut winners: Vec<(u8, BidInfo)> = vec![];
+        let mut skipped_token_ids: Vec<String> = vec![];
+        for (key, bid) in live_bids.into_iter() {
             // Remove bid
-            BIDS.remove(storage, U8Key::from(key[0]));
-            // Create and save Cw721Physical item and increment counter
-            let cw721_physical_id = physicals_count(storage).unwrap() + 1;
-            physicals().save(storage, &U32Key::from(cw721_physical_id).joined_key(), &Cw721PhysicalInfo {
-                id: cw721_physical_id,
-                token_id: bid.token_id.clone(),
-                owner: bid.owner.clone(),
-                tier: 1,
-                status: "PENDING".to_string()
-            })?;
-            increment_physcials(storage)?;
-        }
-        BIDDING_INFO.update(storage, |mut info| -> StdResult<_> {
-            info.start = block.height + info.pause_duration;
-            info.expires = Expiration::AtHeight(info.start + bidding_info.duration);
-            Ok(info)
-        })?;
-        return Ok(Response::default().add_event(Event::new("Resolved Bids")));
+            bids().remove(deps.storage, U8Key::from(key[0]))?;
+            if holds_token(deps.as_ref(), &bid)? {
+                winners.push((key[0], bid));
+            } else {
+                refund_msgs.push(refund_bid_msg(&bid));
+                events.push(bid_invalidated_event(Some(key[0]), &bid, "token_transferred"));
+                skipped_token_ids.push(bid.token_id);
+            }
+        }
+
+        let mut highest_bid = Uint128::zero();
+        let mut physical_ids: Vec<String> = vec![];
+        let mut certificate_msgs: Vec<SubMsg> = vec![];
+        for (slot, bid) in winners.into_iter() {
+            // Previous winners may have taken the last physical item of the token
+            if is_physical_item_available(deps.storage, &bid.owner, &bid.token_id, 1).is_err() {
+                refund_msgs.push(refund_bid_msg(&bid));
+                events.push(bid_invalidated_event(Some(slot), &bid, "not_available"));
+                skipped_token_ids.push(bid.token_id);
+                continue;
+            }
+            // Create and save Cw721Physical item
+            let physical_id = save_physical(deps.storage, block, &bid.token_id, &bid.owner, 1, bid.bid_amount)?;
+            physical_ids.push(physical_id.to_string());
+            certificate_msgs.extend(certificate_mint_msg(deps.storage, physical_id)?);
+            highest_bid = highest_bid.max(bid.bid_amount);
+        }
+
+        // highest runner-ups take the slots of the skipped bids
+        let mut runner_ups = load_runner_up_bids(deps.storage)?;
+        RUNNER_UP_BIDS.remove(deps.storage);
+        runner_ups.sort_by_key(|bid| Reverse(bid.bid_amount));
+        let mut promoted: u32 = 0;
+        for runner_up in runner_ups.iter() {
+            if !bidding_info.promote_runner_ups || promoted as usize == skipped_token_ids.len() {
+                refund_msgs.push(refund_bid_msg(runner_up));
+                continue;
+            }
+            if !holds_token(deps.as_ref(), runner_up)?
+                || is_physical_item_available(deps.storage, &runner_up.owner, &runner_up.token_id, 1).is_err() {
+                refund_msgs.push(refund_bid_msg(runner_up));
+                events.push(bid_invalidated_event(None, runner_up, "not_promotable"));
+                continue;
+            }
+            let physical_id = save_physical(deps.storage, block, &runner_up.token_id, &runner_up.owner, 1, runner_up.bid_amount)?;
+            physical_ids.push(physical_id.to_string());
+            certificate_msgs.extend(certificate_mint_msg(deps.storage, physical_id)?);
+            highest_bid = highest_bid.max(runner_up.bid_amount);
+            promoted += 1;
+        }
+
+        let mut stats = load_stats(deps.storage)?;
+        stats.auction_rounds += 1;
+        stats.highest_winning_bid = stats.highest_winning_bid.max(highest_bid);
+        STATS.save(deps.storage, &stats)?;
+        let next_start = bidding_info.mode.now(block).checked_add(bidding_info.pause_duration)
+            .ok_or(ContractError::InvalidBiddingSchedule {})?;
+        let next = schedule_bidding_window(deps.storage, next_start)?;
+        return Ok(Response::new()
+            .add_submessages(certificate_msgs)
+            .add_messages(refund_msgs)
+            .add_attribute("action", "resolve_bids")
+            .add_events(events)
+            .add_event(Event::new("round_resolved")
+                .add_attribute("round", stats.auction_rounds.to_string())
+                .add_attribute("winning_bids", physical_ids.len().to_string())
+                .add_attribute("physical_ids", physical_ids.join(","))
+                .add_attribute("highest_bid", highest_bid)
+                .add_attribute("skipped_bids", skipped_token_ids.len().to_string())
+                .add_attribute("skipped_token_ids", skipped_token_ids.join(","))
+                .add_attribute("promoted_bids", promoted.to_string())
+                .add_attribute("next_start", next.start.to_string())
+                .add_attribute("next_expires", next.expires.to_string())));
     }
     // Maybe return ContractError (e.g BiddingLive)
     Ok(Response::default())
 }
 
+/// ## Description
+/// Checks if the owner the bid was placed for still holds the token,
+/// escrowed tokens are held by their depositor. Tokens which can't be queried anymore aren't held.
+fn holds_token(deps: Deps, bid: &BidInfo) -> Result<bool, ContractError> {
+    if let Some(depositor) = ESCROWED_TOKENS.may_load(deps.storage, &bid.token_id)? {
+        return Ok(depositor == bid.owner);
+    }
+    Ok(match query_token_owner(deps, &bid.token_id) {
+        Ok(response) => response.owner == bid.owner.as_str(),
+        Err(_) => false
+    })
+}
+
+/// ## Description
+/// Sets the start of the next bidding window, which lasts for the current bidding duration
+/// and takes the staged bids limit.
+/// Returns [`Ok`] with the scheduled window, otherwise returns [`ContractError`] if its end overflows
+fn schedule_bidding_window(storage: &mut dyn Storage, start: u64) -> Result<BiddingInfo, ContractError> {
+    BIDDING_INFO.update(storage, |mut info| -> Result<_, ContractError> {
+        if let Some(bids_limit) = info.next_bids_limit.take() {
+            info.bids_limit = bids_limit;
+        }
+        let end = start.checked_add(info.duration).ok_or(ContractError::InvalidBiddingSchedule {})?;
+        info.start = start;
+        info.expires = info.mode.expiration(end);
+        Ok(info)
+    })
+}
+
+/// ## Description
+/// Reschedules the bidding window, as long as no bids were placed in it.
+/// The window can switch between block height and time based scheduling,
+/// in which case both durations have to be given in units of the new mode.
+/// Returns [`Ok`] if the window was rescheduled, otherwise returns [`ContractError`]
+fn schedule_bidding(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    start: u64,
+    mode: Option<BiddingMode>,
+    duration: Option<u64>,
+    pause_duration: Option<u64>
+) -> Result<Response, ContractError> {
+    let mut bidding_info = BIDDING_INFO.load(storage)?;
+    if let Some(mode) = mode {
+        if mode != bidding_info.mode && (duration.is_none() || pause_duration.is_none()) {
+            return Err(ContractError::BiddingDurationsRequired {});
+        }
+        bidding_info.mode = mode;
+    }
+    if start < bidding_info.mode.now(block) {
+        return Err(ContractError::InvalidBiddingStart {});
+    }
+    if bids().range(storage, None, None, Order::Ascending).next().is_some() {
+        return Err(ContractError::BiddingHasBids {});
+    }
+    if let Some(duration) = duration {
+        if duration == 0 {
+            return Err(ContractError::BiddingDurationIsZero {});
+        }
+        bidding_info.duration = duration;
+    }
+    if let Some(pause_duration) = pause_duration {
+        bidding_info.pause_duration = pause_duration;
+    }
+    BIDDING_INFO.save(storage, &bidding_info)?;
+    let bidding_info = schedule_bidding_window(storage, start)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "schedule_bidding")
+        .add_attribute("mode", bidding_info.mode.to_string())
+        .add_attribute("start", bidding_info.start.to_string())
+        .add_attribute("expires", bidding_info.expires.to_string()))
+}
+
+/// ## Description
+/// Ends the current bidding window without any winners, all bidders get their UST back.
+/// Returns [`Ok`] if the window was aborted, otherwise returns [`ContractError`]
+fn abort_bidding(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    next_start: Option<u64>
+) -> Result<Response, ContractError> {
+    let bidding_info = BIDDING_INFO.load(storage)?;
+    let now = bidding_info.mode.now(block);
+    let next_start = match next_start {
+        Some(start) if start < now => return Err(ContractError::InvalidBiddingStart {}),
+        Some(start) => start,
+        None => now.checked_add(bidding_info.pause_duration).ok_or(ContractError::InvalidBiddingSchedule {})?
+    };
+    let live_bids: Vec<_> = bids()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let mut refund_msgs: Vec<CosmosMsg> = vec![];
+    for (key, bid) in live_bids.iter() {
+        bids().remove(storage, U8Key::from(key[0]))?;
+        refund_msgs.push(refund_bid_msg(bid));
+    }
+    for runner_up in load_runner_up_bids(storage)?.iter() {
+        refund_msgs.push(refund_bid_msg(runner_up));
+    }
+    RUNNER_UP_BIDS.remove(storage);
+    let bidding_info = schedule_bidding_window(storage, next_start)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "abort_bidding")
+        .add_attribute("refunded_bids", refund_msgs.len().to_string())
+        .add_messages(refund_msgs)
+        .add_attribute("start", bidding_info.start.to_string())
+        .add_attribute("expires", bidding_info.expires.to_string()))
+}
+
+/// ## Description
+/// Checks if the sender can act for the owner of the token. Besides the owner itself,
+/// an approved spender of the token or an operator of the owner is allowed.
+/// Delegates need to explicitly name the token owner as the recipient.
+/// Returns the token owner if sender is allowed, otherwise returns [`ContractError`]
+/// ## Params
+/// * **sender** is an object of type [`Addr`]
+/// * **owner_response** is an object of type [`OwnerOfResponse`]
+/// * **recipient** is an object of type [`Option<String>`]
+fn assert_token_delegate(
+    deps: Deps,
+    sender: &Addr,
+    owner_response: &OwnerOfResponse,
+    recipient: Option<String>
+) -> Result<Addr, ContractError> {
+    let owner = Addr::unchecked(owner_response.owner.clone());
+    if let Some(recipient) = recipient.as_ref() {
+        if *recipient != owner {
+            return Err(ContractError::RecipientNotOwner {});
+        }
+    }
+    if *sender == owner {
+        return Ok(owner);
+    }
+
+    let is_approved = owner_response.approvals
+        .iter()
+        .any(|approval| approval.spender == *sender);
+    if !is_approved && !is_cw721_operator(deps, &owner, sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if recipient.is_none() {
+        return Err(ContractError::RecipientRequired {});
+    }
+    Ok(owner)
+}
+
+/// ## Description
+/// Checks if the sender deposited the token to the contract, escrowed tokens can be used
+/// for ordering only by their depositor.
+/// Returns the depositor if sender is allowed, otherwise returns [`ContractError`]
+fn assert_escrow_depositor(
+    storage: &dyn Storage,
+    sender: &Addr,
+    token_id: &str,
+    recipient: Option<String>
+) -> Result<Addr, ContractError> {
+    let depositor = ESCROWED_TOKENS.may_load(storage, token_id)?
+        .ok_or(ContractError::TokenNotEscrowed {})?;
+    if let Some(recipient) = recipient {
+        if recipient != depositor {
+            return Err(ContractError::RecipientNotOwner {});
+        }
+    }
+    if *sender != depositor {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(depositor)
+}
+
 /// ## Description
 /// Verifies that funds sent to contract is UST only
 /// Returns [`Ok`] if only 'UST' is sent to ContractError, otherwise returns [`ContractError`]
 /// ## Params
 /// * **funds** is an object of type [`Vec<Coin>`]
 fn assert_ust(funds: Vec<Coin>) -> Result<(), ContractError> {
-    // Check if funds empty or multiple native coins sent by the user
-    if funds.len() != 1 {
+    // Check if multiple native coins sent by the user
+    if funds.len() > 1 {
         return Err(ContractError::OnlyUSTAccepted {});
     }
     // Only UST accepted
-    let native_token = funds.first().unwrap();
+    let native_token = funds.first().ok_or(ContractError::NoFunds {})?;
     if native_token.denom != *UUSD_DENOM {
         return Err(ContractError::OnlyUSTAccepted {});
     }
@@ -355,13 +1513,24 @@ fn assert_ust(funds: Vec<Coin>) -> Result<(), ContractError> {
 }
 
 /// ## Description
-/// Verify that contract is not paused
-/// Returns [`Ok`] if contract is not paused, otherwise returns [`ContractError`]
+/// Returns the amount of the single native coin sent with the message in [`Ok`],
+/// otherwise returns [`ContractError::NoFunds`]
+/// ## Params
+/// * **funds** is an object of type [`Vec<Coin>`]
+fn sent_ust_amount(funds: &[Coin]) -> Result<Uint128, ContractError> {
+    funds.first()
+        .map(|coin| coin.amount)
+        .ok_or(ContractError::NoFunds {})
+}
+
+/// ## Description
+/// Verify that the operation is not paused
+/// Returns [`Ok`] if the operation is not paused, otherwise returns [`ContractError`]
 /// ## Params
 /// * **storage** is an object of type [`Storage`]
-fn assert_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+fn assert_not_paused(storage: &dyn Storage, operation: PausableOperation) -> Result<(), ContractError> {
     let contract_info = CONTRACT_CONFIG.load(storage)?;
-    if contract_info.paused {
+    if contract_info.paused.contains(&operation) {
         return Err(ContractError::ContractIsPaused {});
     }
     Ok(())
@@ -381,6 +1550,17 @@ fn assert_owner(storage: &dyn Storage, sender: Addr) -> Result<(), ContractError
     Ok(())
 }
 
+/// ## Description
+/// Checks that the sender is the owner or was granted the role.
+/// Returns [`Ok`] if the sender has the role, otherwise returns [`ContractError`]
+fn assert_role(storage: &dyn Storage, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if CONTRACT_CONFIG.load(storage)?.owner == *sender
+        || load_role_members(storage, role)?.contains(sender) {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized {})
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -388,16 +1568,44 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_cw721_address(deps)?),
         QueryMsg::GetCw721PhysicalInfo {token_id} =>
             to_binary(&query_physical_info(deps, token_id)?),
-        QueryMsg::Cw721Physicals {token_id, start_after, limit} =>
-            to_binary(&query_physicals(deps, token_id, start_after, limit)?),
-        QueryMsg::AllCw721Physicals {start_after, limit} =>
-            to_binary(&query_all_physicals(deps, start_after, limit)?),
+        QueryMsg::Cw721Physicals {token_id, start_after, limit, order_by} =>
+            to_binary(&query_physicals(deps, token_id, start_after, limit, order_by)?),
+        QueryMsg::AllCw721Physicals {start_after, limit, order_by} =>
+            to_binary(&query_all_physicals(deps, start_after, limit, order_by)?),
+        QueryMsg::Cw721PhysicalsInfo {token_id, tier, status, start_after, limit, order_by} =>
+            to_binary(&query_physicals_info(deps, token_id, tier, status, start_after, limit, order_by)?),
+        QueryMsg::AllCw721PhysicalsInfo {tier, status, start_after, limit, order_by} =>
+            to_binary(&query_all_physicals_info(deps, tier, status, start_after, limit, order_by)?),
+        QueryMsg::PhysicalsByOwner {owner, start_after, limit} =>
+            to_binary(&query_physicals_by_owner(deps, owner, start_after, limit)?),
+        QueryMsg::PhysicalsByStatus {status, tier, start_after, limit} =>
+            to_binary(&query_physicals_by_status(deps, status, tier, start_after, limit)?),
+        QueryMsg::Config {} =>
+            to_binary(&query_config(deps)?),
+        QueryMsg::Stats {} =>
+            to_binary(&StatsResponse { stats: load_stats(deps.storage)? }),
+        QueryMsg::Roles {} =>
+            to_binary(&query_roles(deps.storage)?),
         QueryMsg::Bids {} =>
             to_binary(&query_bids(deps.storage)?),
+        QueryMsg::BidsByToken {token_id} =>
+            to_binary(&query_bid_slots(deps.storage, bids().idx.token_id.prefix(token_id))?),
+        QueryMsg::BidsByBidder {bidder} =>
+            to_binary(&query_bid_slots(deps.storage, bids().idx.bidder.prefix(deps.api.addr_validate(&bidder)?))?),
         QueryMsg::BiddingInfo {} =>
             to_binary(&query_bidding_info(deps.storage)?),
         QueryMsg::TierInfo {tier} =>
-            to_binary(&query_tier_info(deps, tier)?)
+            to_binary(&query_tier_info(deps, tier)?),
+        QueryMsg::EscrowedToken {token_id} =>
+            to_binary(&query_escrowed_token(deps.storage, token_id)?),
+        QueryMsg::TokenClaims {token_id} =>
+            to_binary(&query_token_claims(deps.storage, token_id)?),
+        QueryMsg::Certificate {physical_id} =>
+            to_binary(&CertificateResponse {
+                certificate: CERTIFICATES.may_load(deps.storage, U32Key::from(physical_id))?
+            }),
+        QueryMsg::PhysicalOwnershipHistory {physical_id} =>
+            to_binary(&query_physical_ownership_history(deps.storage, physical_id)?)
     }
 }
 
@@ -419,8 +1627,49 @@ fn query_cw721_owner(deps: Deps, token_id: String) -> StdResult<OwnerOfResponse>
     Ok(owner)
 }
 
+/// ## Description
+/// Queries the owner of the token from the cw721 contract.
+/// Returns [`OwnerOfResponse`] in [`Ok`], otherwise returns [`ContractError::InvalidTokenId`]
+/// for empty token ids or [`ContractError::Cw721QueryFailed`] if the cw721 query fails
+fn query_token_owner(deps: Deps, token_id: &str) -> Result<OwnerOfResponse, ContractError> {
+    if token_id.trim().is_empty() {
+        return Err(ContractError::InvalidTokenId { token_id: token_id.to_string() });
+    }
+    query_cw721_owner(deps, token_id.to_string())
+        .map_err(|err| ContractError::Cw721QueryFailed { token_id: token_id.to_string(), reason: err.to_string() })
+}
+
+/// ## Description
+/// Pages through the operators of the owner and checks if the sender is among them.
+/// Expired operators are not returned by the cw721 contract.
+fn is_cw721_operator(deps: Deps, owner: &Addr, sender: &Addr) -> StdResult<bool> {
+    let state = CONTRACT_CONFIG.load(deps.storage)?;
+    let mut start_after: Option<String> = None;
+    loop {
+        let response: ApprovedForAllResponse =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: state.cw721.to_string(),
+                msg: to_binary(&ApprovedForAll {
+                    owner: owner.to_string(),
+                    include_expired: None,
+                    start_after,
+                    limit: None
+                })?,
+            }))?;
+        if response.operators.iter().any(|operator| operator.spender == *sender) {
+            return Ok(true);
+        }
+        match response.operators.last() {
+            Some(operator) => start_after = Some(operator.spender.clone()),
+            None => return Ok(false)
+        }
+    }
+}
+
 fn query_physical_info(deps: Deps, physical_id: String) -> StdResult<Cw721PhysicalInfoResponse> {
-    let order_id_int: u32 = physical_id.parse().unwrap();
+    let order_id_int: u32 = physical_id.parse().map_err(|_| {
+        StdError::generic_err(ContractError::InvalidPhysicalId { physical_id: physical_id.clone() }.to_string())
+    })?;
     let physical = physicals().load(deps.storage, &U32Key::from(order_id_int).joined_key())?;
     Ok(Cw721PhysicalInfoResponse { physical })
 }
@@ -428,38 +1677,211 @@ fn query_physical_info(deps: Deps, physical_id: String) -> StdResult<Cw721Physic
 fn query_physicals(
     deps: Deps,
     token_id: String,
-    start_after: Option<String>,
-    limit: Option<u32>
+    start_after: Option<u32>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>
 ) -> StdResult<Cw721PhysicalsResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_id = maybe_addr(deps.api, start_after)?;
-    let start = start_id.map(|id| Bound::exclusive(id.as_ref()));
+    let (min, max, order) = physicals_page_bounds(start_after, order_by);
 
-    let physicals:  StdResult<Vec<String>> = physicals()
+    let ids: StdResult<Vec<u32>> = physicals()
         .idx.token_id
         .prefix(token_id)
-        .range(deps.storage, start, None, Order::Ascending)
-        .map(|item| item.map(|(_, v)| v.id.to_string()))
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, v)| v.id))
         .take(limit)
         .collect();
+    let ids = ids?;
 
-    Ok(Cw721PhysicalsResponse { physicals: physicals? })
+    Ok(Cw721PhysicalsResponse {
+        next_cursor: next_physicals_cursor(&ids, limit),
+        physicals: ids.iter().map(|id| id.to_string()).collect()
+    })
 }
 
 fn query_all_physicals(deps: Deps,
-                       start_after: Option<String>,
-                       limit: Option<u32>
+                       start_after: Option<u32>,
+                       limit: Option<u32>,
+                       order_by: Option<OrderBy>
 ) -> StdResult<AllPhysicalsResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_id = maybe_addr(deps.api, start_after)?;
-    let start = start_id.map(|id| Bound::exclusive(id.as_ref()));
+    let (min, max, order) = physicals_page_bounds(start_after, order_by);
 
-    let physicals: StdResult<Vec<String>> = physicals()
-        .range(deps.storage, start, None, Order::Ascending)
+    let ids: StdResult<Vec<u32>> = physicals()
+        .range(deps.storage, min, max, order)
         .take(limit)
-        .map(|item| item.map(|(_, v)| v.id.to_string()))
+        .map(|item| item.map(|(_, v)| v.id))
         .collect();
-    Ok(AllPhysicalsResponse {physicals: physicals?})
+    let ids = ids?;
+
+    Ok(AllPhysicalsResponse {
+        next_cursor: next_physicals_cursor(&ids, limit),
+        physicals: ids.iter().map(|id| id.to_string()).collect()
+    })
+}
+
+fn query_physicals_info(
+    deps: Deps,
+    token_id: String,
+    tier: Option<u8>,
+    status: Option<PhysicalStatus>,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>
+) -> StdResult<Cw721PhysicalsInfoResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (min, max, order) = physicals_page_bounds(start_after, order_by);
+
+    let items = physicals()
+        .idx.token_id
+        .prefix(token_id)
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, v)| v));
+    let (physicals, next_cursor) = filter_physicals_page(items, tier, status, limit)?;
+
+    Ok(Cw721PhysicalsInfoResponse { physicals, next_cursor })
+}
+
+fn query_all_physicals_info(
+    deps: Deps,
+    tier: Option<u8>,
+    status: Option<PhysicalStatus>,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>
+) -> StdResult<AllPhysicalsInfoResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (min, max, order) = physicals_page_bounds(start_after, order_by);
+
+    // both filters are served by the status and tier index without scanning other items
+    if let (Some(tier), Some(status)) = (tier, status) {
+        let physicals: StdResult<Vec<Cw721PhysicalInfo>> = physicals()
+            .idx.status_tier
+            .prefix((status.to_string(), U8Key::from(tier)))
+            .range(deps.storage, min, max, order)
+            .map(|item| item.map(|(_, v)| v))
+            .take(limit)
+            .collect();
+        let physicals = physicals?;
+        let ids: Vec<u32> = physicals.iter().map(|physical| physical.id).collect();
+        return Ok(AllPhysicalsInfoResponse { physicals, next_cursor: next_physicals_cursor(&ids, limit) });
+    }
+    let items = physicals()
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, v)| v));
+    let (physicals, next_cursor) = filter_physicals_page(items, tier, status, limit)?;
+
+    Ok(AllPhysicalsInfoResponse { physicals, next_cursor })
+}
+
+/// ## Description
+/// Collects a page of physical items matching the tier and status filters. At most
+/// [`MAX_SCAN_LIMIT`] items are scanned, so a page may hold less than `limit` items
+/// while more matching items follow.
+/// Returns the page and the last scanned id as the cursor if there may be more physical items.
+fn filter_physicals_page(
+    items: impl Iterator<Item = StdResult<Cw721PhysicalInfo>>,
+    tier: Option<u8>,
+    status: Option<PhysicalStatus>,
+    limit: usize
+) -> StdResult<(Vec<Cw721PhysicalInfo>, Option<u32>)> {
+    let mut physicals: Vec<Cw721PhysicalInfo> = vec![];
+    let mut scanned: usize = 0;
+    let mut last_scanned: Option<u32> = None;
+    for item in items.take(MAX_SCAN_LIMIT) {
+        let physical = item?;
+        scanned += 1;
+        last_scanned = Some(physical.id);
+        if matches_physical_filter(&physical, tier, status) {
+            physicals.push(physical);
+            if physicals.len() == limit {
+                return Ok((physicals, last_scanned));
+            }
+        }
+    }
+    if scanned < MAX_SCAN_LIMIT {
+        return Ok((physicals, None));
+    }
+    Ok((physicals, last_scanned))
+}
+
+/// ## Description
+/// Physical items are stored under their big-endian encoded id, so the cursor is bound
+/// on the same key. `start_after` is exclusive in both directions.
+/// Returns min and max bounds and the order of the range.
+fn physicals_page_bounds(start_after: Option<u32>, order_by: Option<OrderBy>) -> (Option<Bound>, Option<Bound>, Order) {
+    let start = start_after.map(|id| Bound::exclusive(U32Key::from(id).joined_key()));
+    match order_by.unwrap_or(OrderBy::Ascending) {
+        OrderBy::Ascending => (start, None, Order::Ascending),
+        OrderBy::Descending => (None, start, Order::Descending),
+    }
+}
+
+/// ## Description
+/// Returns the last id of a full page as the cursor for the next page, [`None`] otherwise.
+fn next_physicals_cursor(ids: &[u32], limit: usize) -> Option<u32> {
+    if ids.len() < limit {
+        return None;
+    }
+    ids.last().copied()
+}
+
+fn query_physicals_by_owner(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u32>,
+    limit: Option<u32>
+) -> StdResult<PhysicalsByOwnerResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (min, max, order) = physicals_page_bounds(start_after, None);
+
+    let physicals: StdResult<Vec<Cw721PhysicalInfo>> = physicals()
+        .idx.owner
+        .prefix(owner)
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, v)| v))
+        .take(limit)
+        .collect();
+    let physicals = physicals?;
+    let ids: Vec<u32> = physicals.iter().map(|physical| physical.id).collect();
+
+    Ok(PhysicalsByOwnerResponse { physicals, next_cursor: next_physicals_cursor(&ids, limit) })
+}
+
+fn query_physicals_by_status(
+    deps: Deps,
+    status: PhysicalStatus,
+    tier: u8,
+    start_after: Option<u32>,
+    limit: Option<u32>
+) -> StdResult<PhysicalsByStatusResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (min, max, order) = physicals_page_bounds(start_after, None);
+
+    let physicals: StdResult<Vec<Cw721PhysicalInfo>> = physicals()
+        .idx.status_tier
+        .prefix((status.to_string(), U8Key::from(tier)))
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, v)| v))
+        .take(limit)
+        .collect();
+    let physicals = physicals?;
+    let ids: Vec<u32> = physicals.iter().map(|physical| physical.id).collect();
+
+    Ok(PhysicalsByStatusResponse { physicals, next_cursor: next_physicals_cursor(&ids, limit) })
+}
+
+/// ## Description
+/// Checks if the physical item matches the optional tier and status filters,
+/// errors are kept so they are returned by the query.
+fn matches_physical_filter(
+    physical: &Cw721PhysicalInfo,
+    tier: Option<u8>,
+    status: Option<PhysicalStatus>
+) -> bool {
+    (tier.is_none() || tier == Some(physical.tier))
+        && (status.is_none() || status == Some(physical.status))
 }
 
 fn query_tier_info(deps: Deps, tier: u8) -> StdResult<TierInfoResponse> {
@@ -470,14 +1892,74 @@ fn query_tier_info(deps: Deps, tier: u8) -> StdResult<TierInfoResponse> {
     })
 }
 
+fn query_escrowed_token(storage: &dyn Storage, token_id: String) -> StdResult<EscrowedTokenResponse> {
+    let owner = ESCROWED_TOKENS.may_load(storage, &token_id)?;
+    Ok(EscrowedTokenResponse { owner })
+}
+
+fn query_token_claims(storage: &dyn Storage, token_id: String) -> StdResult<TokenClaimsResponse> {
+    let claims: Vec<TokenClaim> = TOKEN_CLAIMS
+        .prefix(&token_id)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, claim)| claim))
+        .collect::<StdResult<_>>()?;
+    Ok(TokenClaimsResponse { claims })
+}
+
+fn query_physical_ownership_history(storage: &dyn Storage, physical_id: u32) -> StdResult<PhysicalOwnershipHistoryResponse> {
+    let history = PHYSICAL_OWNERS_HISTORY
+        .may_load(storage, U32Key::from(physical_id))?
+        .unwrap_or_default();
+    Ok(PhysicalOwnershipHistoryResponse { history })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONTRACT_CONFIG.load(deps.storage)?;
+    let bidding_info = BIDDING_INFO.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner,
+        cw721: config.cw721,
+        paused: config.paused,
+        escrow_orders: config.escrow_orders,
+        claim_policy: config.claim_policy,
+        certificate: config.certificate,
+        bids_limit: bidding_info.bids_limit,
+        bidding_duration: bidding_info.duration,
+        bidding_pause: bidding_info.pause_duration,
+        bidding_mode: bidding_info.mode,
+        promote_runner_ups: bidding_info.promote_runner_ups,
+        bid_uniqueness: bidding_info.bid_uniqueness
+    })
+}
+
+fn query_roles(storage: &dyn Storage) -> StdResult<RolesResponse> {
+    let roles: StdResult<Vec<RoleMembers>> = ROLES_LIST
+        .iter()
+        .map(|role| Ok(RoleMembers { role: *role, members: load_role_members(storage, *role)? }))
+        .collect();
+    Ok(RolesResponse {
+        owner: CONTRACT_CONFIG.load(storage)?.owner,
+        pending_owner: PENDING_OWNER.may_load(storage)?,
+        roles: roles?
+    })
+}
+
 fn query_bids(storage: &dyn Storage) -> StdResult<BidsResponse> {
-    let bids : Vec<BidInfo> = BIDS
+    let bids : Vec<BidInfo> = bids()
         .range(storage, None, None, Order::Ascending)
         .map(|pair|pair.map(|(_, bid)|bid))
-        .collect::<StdResult<_>>().unwrap();
+        .collect::<StdResult<_>>()?;
     Ok(BidsResponse{bids})
 }
 
+fn query_bid_slots(storage: &dyn Storage, prefix: Prefix<BidInfo>) -> StdResult<BidSlotsResponse> {
+    let bids : Vec<BidSlot> = prefix
+        .range(storage, None, None, Order::Ascending)
+        .map(|pair|pair.map(|(slot, bid)|BidSlot{slot: slot[0], bid}))
+        .collect::<StdResult<_>>()?;
+    Ok(BidSlotsResponse{bids})
+}
+
 fn query_bidding_info(storage: &dyn Storage) -> StdResult<BiddingInfoResponse> {
     let bidding_info = BIDDING_INFO.load(storage)?;
     Ok(BiddingInfoResponse{
@@ -485,6 +1967,10 @@ fn query_bidding_info(storage: &dyn Storage) -> StdResult<BiddingInfoResponse> {
         start: bidding_info.start,
         expiration: bidding_info.expires,
         duration: bidding_info.duration,
-        pause_duration: bidding_info.pause_duration
+        pause_duration: bidding_info.pause_duration,
+        mode: bidding_info.mode,
+        promote_runner_ups: bidding_info.promote_runner_ups,
+        bid_uniqueness: bidding_info.bid_uniqueness,
+        next_bids_limit: bidding_info.next_bids_limit
     })
 }
\ No newline at end of file
@@ -3,13 +3,17 @@ mod tests {
     use cosmwasm_std::testing::{mock_env, mock_info};
     use cosmwasm_std::CosmosMsg::Bank;
     use super::super::testing::mock_dependencies;
-    use cosmwasm_std::{Addr, Attribute, BankMsg, coin, Coin, coins, DepsMut, from_binary, Uint128};
+    use cosmwasm_std::{Addr, Attribute, ContractResult, Event, Order, Reply, ReplyOn, SubMsg, SubMsgExecutionResponse, Storage, Timestamp, BankMsg, coin, Coin, coins, CosmosMsg, DepsMut, from_binary, to_binary, Uint128, WasmMsg};
+    use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+    use cw721_metadata_onchain::{ExecuteMsg as CertificateExecuteMsg, Metadata, MintMsg, Trait, TransferHookMsg};
     use cw0::Expiration;
-    use crate::contract::{execute, instantiate, query};
+    use cw2::{get_contract_version, set_contract_version};
+    use cw_storage_plus::{Map, PrimaryKey, U8Key, U32Key};
+    use crate::contract::{execute, instantiate, migrate, query, reply};
     use crate::error::ContractError;
-    use crate::msg::ExecuteMsg::{Bid721Masterpiece, OrderCw721Print, ResolveBids, UpdateConfig, UpdateTierInfo};
-    use crate::msg::{Cw721AddressResponse, InstantiateMsg, Cw721PhysicalInfoResponse, Cw721PhysicalsResponse, QueryMsg, TierInfoResponse, BidsResponse, BiddingInfoResponse, AllPhysicalsResponse};
-    use crate::state::{BidInfo, Cw721PhysicalInfo, TierInfo};
+    use crate::msg::ExecuteMsg::{AbortBidding, AcceptOwnership, AddRole, Bid721Masterpiece, ScheduleBidding, DropOwnershipProposal, ProposeNewOwner, RemoveRole, WithdrawFunds, OrderCw721Print, OrderCw721Prints, ReceiveNft, ResolveBids, TransferHook, SyncPhysicalOwner, TransferPhysical, UpdateConfig, UpdatePhysicalStatus, UpdateTierInfo, WithdrawNft};
+    use crate::msg::{Cw721AddressResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, Cw721PhysicalInfoResponse, Cw721PhysicalsResponse, QueryMsg, TierInfoResponse, BidsResponse, BidSlot, BidSlotsResponse, BiddingInfoResponse, AllPhysicalsResponse, PrintOrderItem, Cw721HookMsg, Cw721PhysicalsInfoResponse, AllPhysicalsInfoResponse, OrderBy, PhysicalsByOwnerResponse, PhysicalsByStatusResponse, EscrowedTokenResponse, TokenClaimsResponse, PhysicalOwnershipHistoryResponse, StatsResponse, ConfigResponse, RolesResponse, RoleMembers, CertificateResponse};
+    use crate::state::{physicals, BiddingMode, Certificate, PausableOperation, Role, CONTRACT_CONFIG, PHYSICALS_COUNT, PhysicalStatus, BidInfo, BidUniqueness, ClaimPolicy, Cw721PhysicalInfo, OwnershipChange, StatusStats, TierInfo, TierStats, TIERS, TokenClaim};
 
     const CW721_ADDRESS: &str = "cw721-contract";
     const BIDDING_DURATION: u64 = 19440;
@@ -18,7 +22,7 @@ mod tests {
 
     fn setup_contract(deps: DepsMut<'_>){
         let msg = InstantiateMsg {
-            cw721: Addr::unchecked(CW721_ADDRESS),
+            cw721: CW721_ADDRESS.to_string(),
             tier_info: [
                 TierInfo {
                 max_physical_limit: 1,
@@ -35,7 +39,11 @@ mod tests {
             ],
             bids_limit: 1,
             bidding_duration: BIDDING_DURATION,
-            bidding_pause: BIDDING_PAUSE
+            bidding_pause: BIDDING_PAUSE,
+            bidding_mode: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: None
         };
         let info = mock_info("creator", &[]);
         let res = instantiate(deps, mock_env(), info, msg).unwrap();
@@ -49,7 +57,7 @@ mod tests {
         let cw721_address = Addr::unchecked(CW721_ADDRESS);
 
         let instantiate_msg = InstantiateMsg {
-            cw721:  cw721_address.clone(),
+            cw721: CW721_ADDRESS.to_string(),
             tier_info: [
                 TierInfo {
                     max_physical_limit: 1,
@@ -66,7 +74,11 @@ mod tests {
             ],
             bids_limit: 1,
             bidding_duration: BIDDING_DURATION ,
-            bidding_pause: BIDDING_PAUSE
+            bidding_pause: BIDDING_PAUSE,
+            bidding_mode: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: None
         };
         let info = mock_info("creator", &coins(1000, "earth"));
 
@@ -96,7 +108,144 @@ mod tests {
         assert_eq!(instantiate_msg.bids_limit, value.bids_limit);
         assert_eq!(instantiate_msg.bidding_duration, value.duration);
         assert_eq!(instantiate_msg.bidding_pause, value.pause_duration);
-        assert_eq!(Expiration::AtHeight(value.duration + 123_45), value.expiration);
+        assert_eq!(Expiration::AtHeight(value.duration + 12_345), value.expiration);
+    }
+
+    #[test]
+    fn instantiating_with_invalid_cw721_address() {
+        let mut deps = mock_dependencies();
+        for cw721 in ["".to_string(), "a".to_string(), "c".repeat(100)].iter() {
+            let msg = InstantiateMsg {
+                cw721: cw721.clone(),
+                tier_info: [
+                    TierInfo { max_physical_limit: 1, cost: 2500 * 1_000_000 },
+                    TierInfo { max_physical_limit: 10, cost: 120 * 1_000_000 },
+                    TierInfo { max_physical_limit: 3, cost: 0 }
+                ],
+                bids_limit: 1,
+                bidding_duration: BIDDING_DURATION,
+                bidding_pause: BIDDING_PAUSE,
+                bidding_mode: None,
+                escrow_orders: None,
+                claim_policy: None,
+                certificate: None
+            };
+            let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::Std(_)));
+        }
+    }
+
+    #[test]
+    fn instantiating_with_overflowing_bidding_window() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            cw721: CW721_ADDRESS.to_string(),
+            tier_info: [
+                TierInfo { max_physical_limit: 1, cost: 2500 * 1_000_000 },
+                TierInfo { max_physical_limit: 10, cost: 120 * 1_000_000 },
+                TierInfo { max_physical_limit: 3, cost: 0 }
+            ],
+            bids_limit: 1,
+            bidding_duration: u64::MAX,
+            bidding_pause: BIDDING_PAUSE,
+            bidding_mode: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: None
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBiddingSchedule {});
+    }
+
+    #[test]
+    fn updating_config() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let update_msg = |cw721: Option<&str>, bids_limit: Option<u8>, bidding_duration: Option<u64>| UpdateConfig {
+            cw721: cw721.map(|cw721| cw721.to_string()),
+            paused: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: None,
+            bids_limit,
+            bidding_duration,
+            bidding_pause: Some(100),
+            promote_runner_ups: None,
+            bid_uniqueness: None
+        };
+
+        // only admins update the linked contract and bidding params
+        let info = mock_info("random", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, update_msg(Some("new-cw721"), None, None)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // updates without any field to change are rejected for everyone
+        let empty_msg = UpdateConfig { cw721: None, paused: None, escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        for sender in ["random", "creator"].iter() {
+            let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), empty_msg.clone()).unwrap_err();
+            assert_eq!(err, ContractError::EmptyConfigUpdate {});
+        }
+
+        // invalid addresses and zero values are rejected
+        let info = mock_info("creator", &[]);
+        for cw721 in ["a".to_string(), "c".repeat(100)].iter() {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), update_msg(Some(cw721), None, None)).unwrap_err();
+            assert!(matches!(err, ContractError::Std(_)));
+        }
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update_msg(None, Some(0), None)).unwrap_err();
+        assert_eq!(err, ContractError::BidsLimitIsZero {});
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update_msg(None, None, Some(0))).unwrap_err();
+        assert_eq!(err, ContractError::BiddingDurationIsZero {});
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), update_msg(Some("new-cw721"), Some(5), Some(500))).unwrap();
+        assert_eq!(vec![
+            Attribute::new("action", "update_config"),
+            Attribute::new("cw721", "new-cw721"),
+            Attribute::new("next_bids_limit", "5"),
+            Attribute::new("bidding_duration", "500"),
+            Attribute::new("bidding_pause", "100"),
+        ], res.attributes);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(ConfigResponse {
+            owner: Addr::unchecked("creator"),
+            cw721: Addr::unchecked("new-cw721"),
+            paused: vec![],
+            escrow_orders: false,
+            claim_policy: ClaimPolicy::PerOwner,
+            certificate: None,
+            bids_limit: 1,
+            bidding_duration: 500,
+            bidding_pause: 100,
+            bidding_mode: BiddingMode::Height,
+            promote_runner_ups: false,
+            bid_uniqueness: BidUniqueness::default()
+        }, config);
+
+        // linked contract can't change while one of its tokens is escrowed
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: Some(true), claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice".to_string(),
+            token_id: "1".to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {}).unwrap()
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("new-cw721", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, update_msg(Some(CW721_ADDRESS), None, None)).unwrap_err();
+        assert_eq!(err, ContractError::Cw721InUse {});
+
+        // bids limit changed during the live window applies once the next window is scheduled
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!((1, Some(5)), (bidding_info.bids_limit, bidding_info.next_bids_limit));
+        let mut env = mock_env();
+        env.block.height += BIDDING_DURATION;
+        execute(deps.as_mut(), env, mock_info("random", &[]), ResolveBids {}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!((5, None), (bidding_info.bids_limit, bidding_info.next_bids_limit));
     }
 
     #[test]
@@ -148,12 +297,12 @@ mod tests {
 
         // random cannot pause contract or change contract owner
         let info = mock_info("random", &[]);
-        let msg = UpdateConfig { owner: None, paused: Some(true) };
+        let msg = UpdateConfig { cw721: None, paused: Some(PausableOperation::all()), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
         let err =
             execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
                 .unwrap_err();
         assert_eq!(err, ContractError::Unauthorized {});
-        let msg = UpdateConfig { owner: Some(Addr::unchecked("random")), paused: None };
+        let msg = ProposeNewOwner { owner: "random".to_string() };
         let err =
             execute(deps.as_mut(), mock_env(), info, msg.clone())
                 .unwrap_err();
@@ -161,53 +310,395 @@ mod tests {
 
         // owner can pause the contract
         let info = mock_info("creator", &[]);
-        let msg = UpdateConfig { owner: None, paused: Some(true) };
+        let msg = UpdateConfig { cw721: None, paused: Some(PausableOperation::all()), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
         let res =
             execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
         assert_eq!(2, res.attributes.len());
         assert_eq!(Attribute::new("action", "update_config"), res.attributes[0]);
-        assert_eq!(Attribute::new("paused", "true"), res.attributes[1]);
+        assert_eq!(Attribute::new("paused", "ordering_tier_2,ordering_tier_3,bidding,resolving,withdrawals"), res.attributes[1]);
 
         // alice cannot order or bid on physical item
         deps.querier.set_cw721_token("alice", 1);
         let info = mock_info("alice", &[coin(10 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap_err();
         assert_eq!(err, ContractError::ContractIsPaused {});
 
         let alice_bid_funds = coin(2510 * 1000000, "uusd");
-        let info = mock_info("alice", &[alice_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 1.to_string()};
+        let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
             .unwrap_err();
         assert_eq!(err, ContractError::ContractIsPaused {});
 
         // owner can unpause the contract
         let info = mock_info("creator", &[]);
-        let msg = UpdateConfig { owner: None, paused: Some(false) };
+        let msg = UpdateConfig { cw721: None, paused: Some(vec![]), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
         let res =
             execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
         assert_eq!(2, res.attributes.len());
         assert_eq!(Attribute::new("action", "update_config"), res.attributes[0]);
-        assert_eq!(Attribute::new("paused", "false"), res.attributes[1]);
+        assert_eq!(Attribute::new("paused", ""), res.attributes[1]);
 
         // alice can order or bid on physical item
         deps.querier.set_cw721_token("alice", 1);
         let info = mock_info("alice", &[coin(10 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap();
         assert_eq!(0, res.messages.len());
 
         let alice_bid_funds = coin(2510 * 1000000, "uusd");
-        let info = mock_info("alice", &[alice_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 1.to_string()};
+        let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
             .unwrap();
         assert_eq!(0, res.messages.len());
     }
 
+    #[test]
+    fn pausing_single_operations() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        let mut env = mock_env();
+        let info = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // only print tiers can be paused for ordering
+        let owner = mock_info("creator", &[]);
+        let msg = UpdateConfig { cw721: None, paused: Some(vec![PausableOperation::Ordering { tier: 1 }]), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTier {});
+
+        // tier 2 orders and bidding are halted
+        let paused = vec![PausableOperation::Ordering { tier: 2 }, PausableOperation::Bidding];
+        let msg = UpdateConfig { cw721: None, paused: Some(paused.clone()), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(paused, config.paused);
+
+        let info = mock_info("alice", &[coin(130 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "2".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::ContractIsPaused {});
+        // pause is reported before the sent funds are validated
+        for funds in [vec![], coins(130 * 1_000_000, "uluna")].iter() {
+            let info = mock_info("alice", funds);
+            let msg = OrderCw721Print { token_id: "1".to_string(), tier: "2".to_string(), recipient: None };
+            let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert_eq!(err, ContractError::ContractIsPaused {});
+            let info = mock_info("alice", funds);
+            let msg = OrderCw721Prints { items: vec![
+                PrintOrderItem { token_id: "1".to_string(), tier: "2".to_string(), recipient: None },
+            ]};
+            let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert_eq!(err, ContractError::PrintOrderItemFailed {
+                index: 0,
+                token_id: "1".to_string(),
+                error: Box::new(ContractError::ContractIsPaused {})
+            });
+        }
+        let info = mock_info("alice", &[coin(140 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: vec![
+            PrintOrderItem { token_id: "1".to_string(), tier: "3".to_string(), recipient: None },
+            PrintOrderItem { token_id: "1".to_string(), tier: "2".to_string(), recipient: None },
+        ]};
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PrintOrderItemFailed {
+            index: 1,
+            token_id: "1".to_string(),
+            error: Box::new(ContractError::ContractIsPaused {})
+        });
+        let info = mock_info("bob", &[coin(3100 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: Some("alice".to_string()) };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::ContractIsPaused {});
+
+        // tier 3 orders go through and the running auction still settles
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        env.block.height += BIDDING_DURATION;
+        execute(deps.as_mut(), env, mock_info("random", &[]), ResolveBids {}).unwrap();
+        let msg = QueryMsg::AllCw721Physicals { start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!(2, physicals.physicals.len());
+    }
+
+    #[test]
+    fn transferring_ownership() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // nothing to accept without a proposal
+        let info = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, ContractError::NoOwnershipProposal {});
+
+        // alice is proposed, but doesn't own the contract until accepting the proposal
+        let info = mock_info("creator", &[]);
+        let msg = ProposeNewOwner { owner: "alice".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap();
+        let roles: RolesResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("creator"), roles.owner);
+        assert_eq!(Some(Addr::unchecked("alice")), roles.pending_owner);
+
+        // only the proposed owner can accept
+        let info_bob = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info_bob, AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // dropped proposal can't be accepted
+        execute(deps.as_mut(), mock_env(), info.clone(), DropOwnershipProposal {}).unwrap();
+        let info_alice = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info_alice.clone(), AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, ContractError::NoOwnershipProposal {});
+
+        let msg = ProposeNewOwner { owner: "alice".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info_alice.clone(), AcceptOwnership {}).unwrap();
+        assert_eq!(Attribute::new("owner", "alice"), res.attributes[2]);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap();
+        let roles: RolesResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("alice"), roles.owner);
+        assert_eq!(None, roles.pending_owner);
+
+        // previous owner lost its permissions
+        let msg = UpdateTierInfo { tier: 3, max_physical_limit: 100, cost: 10 * 1_000_000 };
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), info_alice, msg).unwrap();
+    }
+
+    #[test]
+    fn granting_roles() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // only the owner grants roles
+        let info = mock_info("alice", &[]);
+        let msg = AddRole { role: Role::Operator, address: "alice".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let owner = mock_info("creator", &[]);
+        for (role, address) in [(Role::Operator, "operator"), (Role::Pauser, "pauser"), (Role::Admin, "admin")].iter() {
+            let msg = AddRole { role: *role, address: address.to_string() };
+            execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Roles {}).unwrap();
+        let roles: RolesResponse = from_binary(&res).unwrap();
+        assert_eq!(RoleMembers { role: Role::Operator, members: vec![Addr::unchecked("operator")] }, roles.roles[1]);
+        assert_eq!(RoleMembers { role: Role::Treasurer, members: vec![] }, roles.roles[3]);
+
+        // operator updates fulfillment status, but can't pause
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let operator = mock_info("operator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::InProduction };
+        execute(deps.as_mut(), mock_env(), operator.clone(), msg).unwrap();
+        let msg = UpdateConfig { cw721: None, paused: Some(PausableOperation::all()), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        let err = execute(deps.as_mut(), mock_env(), operator, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // pauser pauses, but can't change the claim policy
+        let pauser = mock_info("pauser", &[]);
+        execute(deps.as_mut(), mock_env(), pauser.clone(), msg).unwrap();
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: None, claim_policy: Some(ClaimPolicy::PerToken), certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        let err = execute(deps.as_mut(), mock_env(), pauser, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // admin updates config and tiers
+        let admin = mock_info("admin", &[]);
+        execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        let msg = UpdateTierInfo { tier: 3, max_physical_limit: 100, cost: 10 * 1_000_000 };
+        execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+
+        // removed operator loses access
+        let msg = RemoveRole { role: Role::Operator, address: "operator".to_string() };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let operator = mock_info("operator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::Shipped };
+        let err = execute(deps.as_mut(), mock_env(), operator, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn withdrawing_funds() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // contract holds 3000 UST, 2600 of them belong to an active bid
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(2600 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.set_balance(mock_env().contract.address.as_str(), coins(3000 * 1_000_000, "uusd"));
+
+        let owner = mock_info("creator", &[]);
+        let msg = AddRole { role: Role::Treasurer, address: "treasurer".to_string() };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+
+        let info = mock_info("random", &[]);
+        let msg = WithdrawFunds { amount: Uint128::from(100_000_000u128), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let treasurer = mock_info("treasurer", &[]);
+        let msg = WithdrawFunds { amount: Uint128::from(500_000_000u128), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), treasurer.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds { available: 400_000_000, requested: 500_000_000 });
+
+        let msg = WithdrawFunds { amount: Uint128::from(400_000_000u128), recipient: Some("vault".to_string()) };
+        let res = execute(deps.as_mut(), mock_env(), treasurer, msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "vault".to_string(),
+            amount: coins(400_000_000, "uusd")
+        }));
+    }
+
+    #[test]
+    fn rescheduling_bidding() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+
+        let info = mock_info("random", &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, ScheduleBidding { start: 20_000, mode: None, duration: None, pause_duration: None }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // next window can't start in the past
+        let owner = mock_info("creator", &[]);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), ScheduleBidding { start: 12_000, mode: None, duration: None, pause_duration: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBiddingStart {});
+
+        // window can't end beyond the last block height
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), ScheduleBidding { start: u64::MAX - 1, mode: None, duration: None, pause_duration: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBiddingSchedule {});
+
+        // window is postponed, bidding is not possible until it starts
+        execute(deps.as_mut(), env.clone(), owner.clone(), ScheduleBidding { start: 20_000, mode: None, duration: None, pause_duration: None }).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(20_000, bidding_info.start);
+        assert_eq!(Expiration::AtHeight(20_000 + BIDDING_DURATION), bidding_info.expiration);
+
+        deps.querier.set_cw721_token("alice", 1);
+        let alice = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), env.clone(), alice.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BiddingNotAllowed {});
+
+        // window is started early instead
+        execute(deps.as_mut(), env.clone(), owner.clone(), ScheduleBidding { start: env.block.height, mode: None, duration: None, pause_duration: None }).unwrap();
+        execute(deps.as_mut(), env.clone(), alice, msg).unwrap();
+
+        // window with bids keeps its schedule
+        env.block.height += 1;
+        let err = execute(deps.as_mut(), env.clone(), owner, ScheduleBidding { start: 30_000, mode: None, duration: None, pause_duration: None }).unwrap_err();
+        assert_eq!(err, ContractError::BiddingHasBids {});
+    }
+
+    #[test]
+    fn bidding_in_time_based_windows() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+        let now = env.block.time.seconds();
+
+        // switching to time based windows needs durations in seconds
+        let owner = mock_info("creator", &[]);
+        let msg = ScheduleBidding { start: now + 60, mode: Some(BiddingMode::Time), duration: Some(3600), pause_duration: None };
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::BiddingDurationsRequired {});
+        let msg = ScheduleBidding { start: now + 60, mode: Some(BiddingMode::Time), duration: Some(3600), pause_duration: Some(600) };
+        execute(deps.as_mut(), env.clone(), owner, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(BiddingMode::Time, bidding_info.mode);
+        assert_eq!(now + 60, bidding_info.start);
+        assert_eq!(Expiration::AtTime(Timestamp::from_seconds(now + 3660)), bidding_info.expiration);
+
+        // window opens and closes by block time, regardless of block height
+        deps.querier.set_cw721_token("alice", 1);
+        let alice = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), env.clone(), alice.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BiddingNotAllowed {});
+        env.block.time = env.block.time.plus_seconds(60);
+        env.block.height += 1_000_000;
+        execute(deps.as_mut(), env.clone(), alice, msg).unwrap();
+
+        // nothing is resolved before the window ends
+        execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, bids.bids.len());
+
+        // next window starts after the pause in seconds
+        env.block.time = env.block.time.plus_seconds(3600);
+        execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(env.block.time.seconds() + 600, bidding_info.start);
+        assert_eq!(Expiration::AtTime(Timestamp::from_seconds(env.block.time.seconds() + 4200)), bidding_info.expiration);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, bids.bids.len());
+    }
+
+    #[test]
+    fn aborting_bidding() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let env = mock_env();
+
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let info = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, AbortBidding { next_start: None }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // bids are refunded and the next window starts after the bidding pause
+        let owner = mock_info("creator", &[]);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), AbortBidding { next_start: None }).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(3000 * 1_000_000, "uusd")
+        }));
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, bids.bids.len());
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(env.block.height + BIDDING_PAUSE, bidding_info.start);
+
+        // aborted window doesn't count as an auction round and creates no physical items
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap();
+        let stats: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, stats.stats.auction_rounds);
+        assert_eq!(0, stats.stats.tiers[0].physicals);
+
+        // next window can be set explicitly
+        execute(deps.as_mut(), env.clone(), owner, AbortBidding { next_start: Some(env.block.height + 10) }).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BiddingInfo {}).unwrap();
+        let bidding_info: BiddingInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(env.block.height + 10, bidding_info.start);
+    }
+
     #[test]
     fn processing_bids_after_bidding_window_expires() {
         let mut deps = mock_dependencies();
@@ -217,9 +708,9 @@ mod tests {
 
         // alice places masterpiece bid
         let  alice_bid_funds = coin(3000 * 1_000_000, "uusd");
-        let info = mock_info("alice", &[alice_bid_funds.clone()]);
+        let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
         let mut env = mock_env();
-        let msg = Bid721Masterpiece { token_id: 1.to_string()};
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: None };
         let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
         assert_eq!(0, res.messages.len());
 
@@ -237,7 +728,7 @@ mod tests {
         let res = query(
             deps.as_ref(),
             mock_env(),
-            QueryMsg::AllCw721Physicals {start_after: None, limit: None}).unwrap();
+            QueryMsg::AllCw721Physicals {start_after: None, limit: None, order_by: None }).unwrap();
         let physicals: AllPhysicalsResponse = from_binary(&res).unwrap();
         assert_eq!(1, physicals.physicals.len());
         // Check updated Bidding Information
@@ -257,7 +748,7 @@ mod tests {
 
         // random cannot create order
         let info = mock_info("chuck", &[coin(130 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string()};
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
         let err =
             execute(deps.as_mut(), mock_env(), info, msg.clone())
                 .unwrap_err();
@@ -265,7 +756,7 @@ mod tests {
 
         // alice can order tier 3 physical-print
         let info = mock_info("alice", &[coin(10 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap();
         assert_eq!(0, res.messages.len());
@@ -279,25 +770,28 @@ mod tests {
             token_id: "1".to_string(),
             owner: Addr::unchecked("alice"),
             tier: 3,
-            status: "PENDING".to_string()
+            status: PhysicalStatus::Pending,
+            detached: false,
+            created_at: mock_env().block.time,
+            updated_at: mock_env().block.time
         }, pyhsical.physical);
 
         // alice cannot order physical-print of same tier twice
         let info = mock_info("alice", &[coin(10 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap_err();
         assert_eq!(err, ContractError::AlreadyOwned {});
 
         // alice can still order tier 2 physical-print
         let info = mock_info("alice", &[coin(130 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 2.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 2.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap();
         assert_eq!(0, res.messages.len());
 
         // query all orders
-        let query_order_msg = QueryMsg::AllCw721Physicals { start_after: None, limit: None };
+        let query_order_msg = QueryMsg::AllCw721Physicals { start_after: None, limit: None, order_by: None };
         let res = query(deps.as_ref(),mock_env(), query_order_msg).unwrap();
         let physicals: Cw721PhysicalsResponse = from_binary(&res).unwrap();
         assert_eq!(2, physicals.physicals.len());
@@ -311,18 +805,18 @@ mod tests {
 
         // cannot create order with wrong tier(=0)
         let info = mock_info("alice", &[coin(2510 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 0.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 0.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::InvalidTier {});
 
         // tier = 1
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 1.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 1.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
             .unwrap_err();
         assert_eq!(err, ContractError::InvalidTier {});
 
         // tier = 4
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 4.to_string()};
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 4.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::InvalidTier {});
     }
@@ -336,31 +830,31 @@ mod tests {
 
         // cannot create tier 3 order with non UST denom
         let info = mock_info("alice", &[coin(10 * 1_000_000, "snow")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string() };
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::OnlyUSTAccepted {});
 
         // cannot create tier 3 order with sending multiple tokens
         let info = mock_info("alice", &[
             coin(10 * 1_000_000, "uusd"),
-            coin(1 * 1_000_000, "uluna")
+            coin(1_000_000, "uluna")
         ]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string() };
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::OnlyUSTAccepted {});
 
         // cannot create tier 3 order with 1 UST
-        let info = mock_info("alice", &[coin(1 * 1_000_000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string() };
+        let info = mock_info("alice", &[coin(1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::InvalidUSTAmount {
             required: 10 * 1_000_000,
-            sent: 1 * 1_000_000
+            sent: 1_000_000
         });
 
         // cannot create tier 3 order with 200 UST
         let info = mock_info("alice", &[coin(200 * 1_000_000, "uusd")]);
-        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string() };
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
         assert_eq!(err, ContractError::InvalidUSTAmount {
             required: 10 * 1_000_000,
@@ -368,6 +862,50 @@ mod tests {
         });
     }
 
+    #[test]
+    fn rejecting_invalid_inputs() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+
+        // order without funds
+        let info = mock_info("alice", &[]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+
+        // order with unparsable tier
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "three".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTier {});
+
+        // order with empty token id
+        let msg = OrderCw721Print { token_id: "".to_string(), tier: "3".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTokenId { token_id: "".to_string() });
+
+        // order for token unknown to the cw721 contract
+        let msg = OrderCw721Print { token_id: "2".to_string(), tier: "3".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Cw721QueryFailed { token_id, .. } if token_id == "2"));
+
+        // bid without funds and for unknown token
+        let info = mock_info("alice", &[]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+        let info = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "2".to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Cw721QueryFailed { token_id, .. } if token_id == "2"));
+
+        // query physical item with non numeric id
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetCw721PhysicalInfo { token_id: "first".to_string() }).unwrap_err();
+        assert_eq!(err.to_string(), format!("Generic error: {}", ContractError::InvalidPhysicalId { physical_id: "first".to_string() }));
+    }
+
     #[test]
     fn ordering_max_possible_physical_items_per_token() {
         let mut deps = mock_dependencies();
@@ -395,11 +933,11 @@ mod tests {
             };
             let info = mock_info("alice", &[ust]);
             // creates an order
-            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string()};
+            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string(), recipient: None };
             let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
             assert_eq!(0, res.messages.len());
             // can't have a duplicate physical item
-            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string()};
+            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string(), recipient: None };
             let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
             assert_eq!(err, ContractError::AlreadyOwned {});
         }
@@ -414,7 +952,7 @@ mod tests {
                 _ => coin(10 * 1000000, "uusd")
             };
             let info = mock_info("bob", &[ust]);
-            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string()};
+            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string(), recipient: None };
             let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
             match x {
                 2 => assert_eq!(err, ContractError::MaxTier2Items {}),
@@ -441,12 +979,499 @@ mod tests {
                 _ => coin(10 * 1000000, "uusd")
             };
             let info = mock_info("bob", &[ust]);
-            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string()};
+            let msg = OrderCw721Print { token_id: 1.to_string(), tier: x.to_string(), recipient: None };
             let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
             assert_eq!(0, res.messages.len());
         }
     }
 
+    #[test]
+    fn ordering_prints_in_batch() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("alice", 2);
+        deps.querier.set_cw721_token("bob", 3);
+
+        let items = vec![
+            PrintOrderItem { token_id: "1".to_string(), tier: "2".to_string(), recipient: None },
+            PrintOrderItem { token_id: "1".to_string(), tier: "3".to_string(), recipient: None },
+            PrintOrderItem { token_id: "2".to_string(), tier: "3".to_string(), recipient: None },
+        ];
+
+        // empty batch is rejected
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyPrintOrder {});
+
+        // exact sum of all items is required
+        let info = mock_info("alice", &[coin(130 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: items.clone() };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidUSTAmount {
+            required: 150 * 1_000_000,
+            sent: 130 * 1_000_000
+        });
+
+        // invalid tier is reported with the offending item
+        let mut invalid_items = items.clone();
+        invalid_items[1].tier = "1".to_string();
+        let info = mock_info("alice", &[coin(150 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: invalid_items };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PrintOrderItemFailed {
+            index: 1,
+            token_id: "1".to_string(),
+            error: Box::new(ContractError::InvalidTier {})
+        });
+
+        // alice cannot order a print of bob's token
+        let mut invalid_items = items.clone();
+        invalid_items[2].token_id = "3".to_string();
+        let info = mock_info("alice", &[coin(150 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: invalid_items };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PrintOrderItemFailed {
+            index: 2,
+            token_id: "3".to_string(),
+            error: Box::new(ContractError::Unauthorized {})
+        });
+
+        // duplicate item inside the batch fails
+        let mut invalid_items = items.clone();
+        invalid_items.push(PrintOrderItem { token_id: "2".to_string(), tier: "3".to_string(), recipient: None });
+        let info = mock_info("alice", &[coin(160 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: invalid_items };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PrintOrderItemFailed {
+            index: 3,
+            token_id: "2".to_string(),
+            error: Box::new(ContractError::AlreadyOwned {})
+        });
+
+        // alice orders all prints at once
+        let info = mock_info("alice", &[coin(150 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let query_order_msg = QueryMsg::AllCw721Physicals { start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), query_order_msg).unwrap();
+        let physicals: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["1", "2", "3"], physicals.physicals);
+
+        let query_order_msg = QueryMsg::GetCw721PhysicalInfo { token_id: 3.to_string() };
+        let res = query(deps.as_ref(), mock_env(), query_order_msg).unwrap();
+        let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Cw721PhysicalInfo {
+            id: 3,
+            token_id: "2".to_string(),
+            owner: Addr::unchecked("alice"),
+            tier: 3,
+            status: PhysicalStatus::Pending,
+            detached: false,
+            created_at: mock_env().block.time,
+            updated_at: mock_env().block.time
+        }, physical.physical);
+    }
+
+    #[test]
+    fn ordering_and_bidding_by_delegates() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("dave", 2);
+        // bob is approved to spend token 1, carol is operator of all alice's tokens
+        deps.querier.set_cw721_approval("bob", 1);
+        deps.querier.set_cw721_operator("alice", "carol");
+
+        // random cannot order on alice's behalf
+        let info = mock_info("chuck", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: Some("alice".to_string()) };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // delegate needs to name the owner as recipient
+        let info = mock_info("bob", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::RecipientRequired {});
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: Some("bob".to_string()) };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::RecipientNotOwner {});
+
+        // approved spender orders tier 3 print for alice
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: Some("alice".to_string()) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // operator orders tier 2 print for alice
+        let info = mock_info("carol", &[coin(130 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 2.to_string(), recipient: Some("alice".to_string()) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // physical items are recorded against alice
+        for id in 1..3 {
+            let msg = QueryMsg::GetCw721PhysicalInfo { token_id: id.to_string() };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+            assert_eq!(Addr::unchecked("alice"), physical.physical.owner);
+        }
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyOwned {});
+
+        // operator bids for alice
+        let carol_bid_funds = coin(2510 * 1_000_000, "uusd");
+        let info = mock_info("carol", std::slice::from_ref(&carol_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: Some("alice".to_string()) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![BidInfo {
+            bid_amount: carol_bid_funds.amount,
+            token_id: "1".to_string(),
+            owner: Addr::unchecked("alice"),
+            bidder: Addr::unchecked("carol")
+        }], bids.bids);
+
+        // dave overbids, carol who paid the bid gets refunded
+        let info = mock_info("dave", &[coin(2600 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: 2.to_string(), recipient: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(Bank(BankMsg::Send {
+            to_address: "carol".to_string(),
+            amount: vec![carol_bid_funds],
+        }), res.messages[0].msg);
+    }
+
+    #[test]
+    fn ordering_prints_with_escrowed_tokens() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let deposit_msg = |sender: &str, token_id: &str| ReceiveNft(Cw721ReceiveMsg {
+            sender: sender.to_string(),
+            token_id: token_id.to_string(),
+            msg: to_binary(&Cw721HookMsg::Deposit {}).unwrap()
+        });
+
+        // tokens can't be deposited while escrow is disabled
+        let info = mock_info(CW721_ADDRESS, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, deposit_msg("alice", "1")).unwrap_err();
+        assert_eq!(err, ContractError::EscrowDisabled {});
+
+        // owner enables escrowed orders
+        let info = mock_info("creator", &[]);
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: Some(true), claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(Attribute::new("escrow_orders", "true"), res.attributes[1]);
+
+        // alice cannot order before depositing the token
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::TokenNotEscrowed {});
+
+        // only the cw721 contract can deposit tokens
+        let info = mock_info("chuck", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, deposit_msg("chuck", "1")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // alice sends her token to the contract
+        let info = mock_info(CW721_ADDRESS, &[]);
+        execute(deps.as_mut(), mock_env(), info, deposit_msg("alice", "1")).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::EscrowedToken { token_id: "1".to_string() }).unwrap();
+        let escrowed: EscrowedTokenResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(Addr::unchecked("alice")), escrowed.owner);
+
+        // deposits sent by an operator instead of the owner are rejected by the transfer hook
+        let info = mock_info(CW721_ADDRESS, &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), deposit_msg("olivia", "3")).unwrap();
+        let hook_msg = |sender: &str| TransferHook(TransferHookMsg {
+            sender: sender.to_string(),
+            previous_owner: "bob".to_string(),
+            recipient: mock_env().contract.address.to_string(),
+            token_id: "3".to_string()
+        });
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), hook_msg("olivia")).unwrap_err();
+        assert_eq!(err, ContractError::DepositNotByOwner {});
+        execute(deps.as_mut(), mock_env(), info, hook_msg("bob")).unwrap();
+
+        let info = mock_info("bob", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // alice orders tier 3 and tier 2 prints
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("alice", &[coin(130 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 2.to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // token stays locked while prints are in production
+        let info = mock_info("alice", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, WithdrawNft { token_id: "1".to_string() }).unwrap_err();
+        assert_eq!(err, ContractError::TokenLocked {});
+
+        // only known statuses are accepted
+        let info = mock_info("creator", &[]);
+        let msg = br#"{"update_physical_status":{"physical_id":1,"status":"LOST"}}"#;
+        assert!(from_binary::<ExecuteMsg>(&msg.into()).is_err());
+
+        // first print is delivered, second one is still pending
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::Delivered };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // cancelling the last print returns the token to alice
+        let msg = UpdatePhysicalStatus { physical_id: 2, status: PhysicalStatus::Cancelled };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: CW721_ADDRESS.to_string(),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "alice".to_string(),
+                token_id: "1".to_string()
+            }).unwrap(),
+            funds: vec![]
+        }), res.messages[0].msg);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::EscrowedToken { token_id: "1".to_string() }).unwrap();
+        let escrowed: EscrowedTokenResponse = from_binary(&res).unwrap();
+        assert_eq!(None, escrowed.owner);
+
+        // delivered or cancelled items are final
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::Pending };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::PhysicalItemClosed {});
+
+        // token without open orders can be withdrawn by its depositor only
+        let info = mock_info(CW721_ADDRESS, &[]);
+        execute(deps.as_mut(), mock_env(), info, deposit_msg("alice", "2")).unwrap();
+        let info = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, WithdrawNft { token_id: "2".to_string() }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info("alice", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, WithdrawNft { token_id: "2".to_string() }).unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn claiming_tiers_per_token() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // owner switches to claims per token
+        let info = mock_info("creator", &[]);
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: None, claim_policy: Some(ClaimPolicy::PerToken), certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(Attribute::new("claim_policy", "per_token"), res.attributes[1]);
+
+        // alice claims tier 3 print of token 1
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // alice sells the token, bob cannot claim tier 3 print again
+        deps.querier.transfer_cw721_token("bob", 1);
+        let info = mock_info("bob", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::TierAlreadyClaimed {});
+
+        // claim is recorded against alice
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenClaims { token_id: "1".to_string() }).unwrap();
+        let claims: TokenClaimsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![TokenClaim {
+            physical_id: 1,
+            tier: 3,
+            owner: Addr::unchecked("alice")
+        }], claims.claims);
+
+        // cancelled physical item releases the claim
+        let creator = mock_info("creator", &[]);
+        let status_msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::Cancelled };
+        execute(deps.as_mut(), mock_env(), creator, status_msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenClaims { token_id: "1".to_string() }).unwrap();
+        let claims: TokenClaimsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, claims.claims.len());
+
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenClaims { token_id: "1".to_string() }).unwrap();
+        let claims: TokenClaimsResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("bob"), claims.claims[0].owner);
+
+        // cancelled physical item neither blocks its owner nor counts towards the tier limit
+        let creator = mock_info("creator", &[]);
+        let status_msg = UpdatePhysicalStatus { physical_id: 2, status: PhysicalStatus::Cancelled };
+        execute(deps.as_mut(), mock_env(), creator.clone(), status_msg).unwrap();
+        let tier_msg = UpdateTierInfo { tier: 3, max_physical_limit: 1, cost: 0 };
+        execute(deps.as_mut(), mock_env(), creator, tier_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenClaims { token_id: "1".to_string() }).unwrap();
+        let claims: TokenClaimsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![TokenClaim {
+            physical_id: 3,
+            tier: 3,
+            owner: Addr::unchecked("bob")
+        }], claims.claims);
+    }
+
+    #[test]
+    fn syncing_and_transferring_physical_owner() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // owner is already in sync
+        let info = mock_info("random", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), SyncPhysicalOwner { physical_id: 1 }).unwrap();
+        assert_eq!(Attribute::new("changed", "false"), res.attributes[3]);
+
+        // alice sells the token to bob, anyone can sync the physical item owner
+        deps.querier.transfer_cw721_token("bob", 1);
+        let res = execute(deps.as_mut(), mock_env(), info, SyncPhysicalOwner { physical_id: 1 }).unwrap();
+        assert_eq!(Attribute::new("owner", "bob"), res.attributes[2]);
+        assert_eq!(Attribute::new("changed", "true"), res.attributes[3]);
+
+        let msg = QueryMsg::GetCw721PhysicalInfo { token_id: 1.to_string() };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("bob"), physical.physical.owner);
+
+        // claims stay with alice, so bob can still order the tier for himself once
+        let info = mock_info("bob", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: 1.to_string(), tier: 3.to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyOwned {});
+
+        // only the holder can transfer the physical item
+        let info = mock_info("alice", &[]);
+        let msg = TransferPhysical { physical_id: 1, recipient: "carol".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let info = mock_info("bob", &[]);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // transferred physical item doesn't follow the token anymore
+        let info = mock_info("random", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, SyncPhysicalOwner { physical_id: 1 }).unwrap_err();
+        assert_eq!(err, ContractError::PhysicalItemDetached {});
+
+        let msg = QueryMsg::PhysicalOwnershipHistory { physical_id: 1 };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let history: PhysicalOwnershipHistoryResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![
+            OwnershipChange {
+                previous_owner: Addr::unchecked("alice"),
+                owner: Addr::unchecked("bob"),
+                block_height: mock_env().block.height
+            },
+            OwnershipChange {
+                previous_owner: Addr::unchecked("bob"),
+                owner: Addr::unchecked("carol"),
+                block_height: env.block.height
+            }
+        ], history.history);
+    }
+
+    #[test]
+    fn handling_transfer_hooks() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let env = mock_env();
+
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let info = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let hook = |recipient: &str| TransferHook(TransferHookMsg {
+            sender: "alice".to_string(),
+            previous_owner: "alice".to_string(),
+            recipient: recipient.to_string(),
+            token_id: "1".to_string()
+        });
+
+        // only notifications of the cw721 contract are handled
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), hook("carol")).unwrap();
+        assert_eq!(Attribute::new("ignored", "unknown_cw721"), res.attributes[2]);
+        assert_eq!(0, res.messages.len());
+
+        // escrow deposits don't change the owner
+        let res = execute(deps.as_mut(), env.clone(), mock_info(CW721_ADDRESS, &[]), hook(env.contract.address.as_str())).unwrap();
+        assert_eq!(Attribute::new("physicals_updated", "0"), res.attributes[3]);
+        assert_eq!(0, res.messages.len());
+
+        // physical item follows the token and the bid of the previous owner is refunded
+        let res = execute(deps.as_mut(), env.clone(), mock_info(CW721_ADDRESS, &[]), hook("carol")).unwrap();
+        assert_eq!(vec![
+            Attribute::new("action", "transfer_hook"),
+            Attribute::new("token_id", "1"),
+            Attribute::new("recipient", "carol"),
+            Attribute::new("physicals_updated", "1"),
+            Attribute::new("bids_invalidated", "1"),
+        ], res.attributes);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(3000 * 1_000_000, "uusd")
+        }));
+        assert_eq!("bid_invalidated", res.events[0].ty);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetCw721PhysicalInfo { token_id: "1".to_string() }).unwrap();
+        let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carol"), physical.physical.owner);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::PhysicalOwnershipHistory { physical_id: 1 }).unwrap();
+        let history: PhysicalOwnershipHistoryResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![OwnershipChange {
+            previous_owner: Addr::unchecked("alice"),
+            owner: Addr::unchecked("carol"),
+            block_height: env.block.height
+        }], history.history);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, bids.bids.len());
+
+        // freed slot can be taken by the new owner
+        deps.querier.transfer_cw721_token("carol", 1);
+        let info = mock_info("carol", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(Attribute::new("slot", "1"), res.events[0].attributes[0]);
+
+        // previously linked cw721 keeps notifying transfers, which neither fail nor change anything
+        let creator = mock_info("creator", &[]);
+        let msg = UpdateConfig { cw721: Some("new-cw721".to_string()), paused: None, escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info(CW721_ADDRESS, &[]), hook("dave")).unwrap();
+        assert_eq!(Attribute::new("ignored", "unknown_cw721"), res.attributes[2]);
+        assert_eq!(0, res.messages.len());
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetCw721PhysicalInfo { token_id: "1".to_string() }).unwrap();
+        let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carol"), physical.physical.owner);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Bids {}).unwrap();
+        let bids: BidsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, bids.bids.len());
+    }
+
     #[test]
     fn query_physicals_by_token_id() {
         let mut deps = mock_dependencies();
@@ -457,22 +1482,538 @@ mod tests {
 
         // alice orders tier 3 and tier 2 physical items
         let info = mock_info("alice", &[coin(10 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string()};
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
         assert_eq!(0, res.messages.len());
         let info = mock_info("alice", &[coin(130 * 1000000, "uusd")]);
-        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "2".to_string()};
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "2".to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
         assert_eq!(0, res.messages.len());
 
         // query alice's physical orders by token ID
-        let query_physicals_msg = QueryMsg::Cw721Physicals {token_id: "1".to_string(), start_after: None, limit: None };
+        let query_physicals_msg = QueryMsg::Cw721Physicals {token_id: "1".to_string(), start_after: None, limit: None, order_by: None };
         let res = query(deps.as_ref(),mock_env(), query_physicals_msg).unwrap();
         let physicals: Cw721PhysicalsResponse = from_binary(&res).unwrap();
         assert_eq!(2, physicals.physicals.len());
         assert_eq!(vec!["1", "2"], physicals.physicals);
     }
 
+    #[test]
+    fn allocating_physical_ids() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("alice", 2);
+        deps.querier.set_cw721_token("bob", 3);
+
+        // single order, batch order and a resolved bid all take ids from the same allocator
+        let info = mock_info("alice", &[coin(130 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "2".to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("alice", &[coin(20 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Prints { items: vec![
+            PrintOrderItem { token_id: "1".to_string(), tier: "3".to_string(), recipient: None },
+            PrintOrderItem { token_id: "2".to_string(), tier: "3".to_string(), recipient: None },
+        ]};
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        let info = mock_info("bob", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        env.block.height += BIDDING_DURATION;
+        execute(deps.as_mut(), env, info, ResolveBids {}).unwrap();
+
+        assert_eq!(4, PHYSICALS_COUNT.load(&deps.storage).unwrap());
+
+        // every record in the physicals namespace is keyed by its own id, index entries live elsewhere
+        let mut prefix = vec![0u8, "physicals".len() as u8];
+        prefix.extend_from_slice(b"physicals");
+        let ids: Vec<u32> = deps.storage
+            .range(None, None, Order::Ascending)
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| {
+                let physical: Cw721PhysicalInfo = from_binary(&value.into()).unwrap();
+                assert_eq!(key[prefix.len()..], physical.id.to_be_bytes());
+                physical.id
+            })
+            .collect();
+        assert_eq!(vec![1, 2, 3, 4], ids);
+    }
+
+    #[test]
+    fn migrating_physical_ids() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let owners = ["alice", "bob", "carol"];
+        for (token_id, owner) in owners.iter().enumerate() {
+            deps.querier.set_cw721_token(owner, token_id as u128 + 1);
+        }
+        for (token_id, owner) in owners[..2].iter().enumerate() {
+            let info = mock_info(owner, &[coin(10 * 1_000_000, "uusd")]);
+            let msg = OrderCw721Print { token_id: (token_id + 1).to_string(), tier: "3".to_string(), recipient: None };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // 0.3.0 stored bids without the token and bidder indexes
+        set_contract_version(&mut deps.storage, "crates.io:cw721-nfc", "0.3.0").unwrap();
+        let bid = BidInfo {
+            bid_amount: Uint128::from(2600 * 1_000_000u128),
+            owner: Addr::unchecked("carol"),
+            bidder: Addr::unchecked("carol"),
+            token_id: "3".to_string()
+        };
+        Map::<U8Key, BidInfo>::new("bids").save(&mut deps.storage, U8Key::from(1), &bid).unwrap();
+
+        // allocator fell behind the stored items, migration moves it past the highest id
+        PHYSICALS_COUNT.save(&mut deps.storage, &1).unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(Attribute::new("physicals_count", "2"), res.attributes[3]);
+        assert_eq!(2, PHYSICALS_COUNT.load(&deps.storage).unwrap());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByBidder { bidder: "carol".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![BidSlot { slot: 1, bid }], slots.bids);
+
+        let info = mock_info("carol", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "3".to_string(), tier: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(3, PHYSICALS_COUNT.load(&deps.storage).unwrap());
+
+        // the unique id index rejects duplicated ids, an item stored under a foreign id is reported as a collision
+        let mut physical = physicals().load(&deps.storage, &3u32.to_be_bytes()).unwrap();
+        physical.id = 2;
+        physicals().save(&mut deps.storage, &5u32.to_be_bytes(), &physical).unwrap_err();
+        physical.id = 7;
+        physicals().save(&mut deps.storage, &5u32.to_be_bytes(), &physical).unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(ContractError::PhysicalIdCollision { id: 7 }, err);
+    }
+
+    #[test]
+    fn migrating_from_legacy_state() {
+        let mut deps = mock_dependencies();
+
+        // 0.1.0 stored its config under the cw2 version key and statuses as plain strings
+        deps.storage.set(b"contract_info", br#"{"owner":"creator","cw721":"cw721-contract","paused":true}"#);
+        deps.storage.set(b"physicals_count", b"2");
+        let legacy_physicals = [
+            (1u32, br#"{"id":1,"token_id":"1","owner":"alice","tier":3,"status":"PENDING"}"#.to_vec()),
+            (2u32, br#"{"id":2,"token_id":"2","owner":"bob","tier":3,"status":"shipped"}"#.to_vec()),
+        ];
+        for (id, value) in legacy_physicals.iter() {
+            let mut key = vec![0u8, "physicals".len() as u8];
+            key.extend_from_slice(b"physicals");
+            key.extend_from_slice(&id.to_be_bytes());
+            deps.storage.set(&key, value);
+        }
+        // live bid of the round, placed by the token owner
+        TIERS.save(&mut deps.storage, U8Key::from(1), &TierInfo { max_physical_limit: 1, cost: 2500 * 1_000_000 }).unwrap();
+        deps.storage.set(b"bidding_info", br#"{"bids_limit":1,"duration":19440,"pause_duration":71280,"start":12345,"expires":{"at_height":31785}}"#);
+        let mut key = vec![0u8, "bids".len() as u8];
+        key.extend_from_slice(b"bids");
+        key.push(1);
+        deps.storage.set(&key, br#"{"bid_amount":"2600000000","token_id":"3","owner":"carol"}"#);
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(Attribute::new("from_version", "0.1.0"), res.attributes[1]);
+
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!("crates.io:cw721-nfc", version.contract);
+        assert_eq!(env!("CARGO_PKG_VERSION"), version.version);
+        let config = CONTRACT_CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(Addr::unchecked("creator"), config.owner);
+        assert!(!config.escrow_orders);
+        assert_eq!(PausableOperation::all(), config.paused);
+        assert_eq!(ClaimPolicy::PerOwner, config.claim_policy);
+
+        // statuses are typed and the status and owner indexes are rebuilt
+        let msg = QueryMsg::PhysicalsByStatus { status: PhysicalStatus::Shipped, tier: 3, start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![2], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        let msg = QueryMsg::PhysicalsByOwner { owner: "alice".to_string(), start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(PhysicalStatus::Pending, page.physicals[0].status);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenClaims { token_id: "1".to_string() }).unwrap();
+        let claims: TokenClaimsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![TokenClaim { physical_id: 1, tier: 3, owner: Addr::unchecked("alice") }], claims.claims);
+
+        // stats are rebuilt from the existing physical items
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap();
+        let stats: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(TierStats { tier: 3, physicals: 2, revenue: Uint128::zero() }, stats.stats.tiers[2]);
+        assert_eq!(2, stats.stats.unique_orderers);
+        assert_eq!(2, PHYSICALS_COUNT.load(&deps.storage).unwrap());
+
+        // legacy bid is indexed by its owner as bidder and resolves into a physical item
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByBidder { bidder: "carol".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("carol"), slots.bids[0].bid.owner);
+        deps.querier.set_cw721_token("carol", 3);
+        let msg = UpdateConfig { cw721: None, paused: Some(vec![]), escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: None, bid_uniqueness: None };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 31_785;
+        execute(deps.as_mut(), env, mock_info("random", &[]), ResolveBids {}).unwrap();
+        let msg = QueryMsg::PhysicalsByOwner { owner: "carol".to_string(), start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![(3, "3".to_string(), 1)], page.physicals.iter().map(|p| (p.id, p.token_id.clone(), p.tier)).collect::<Vec<_>>());
+
+        // migrating again on the same version is a no-op
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    }
+
+    #[test]
+    fn rejecting_invalid_migrations() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        set_contract_version(&mut deps.storage, "crates.io:cw721-nfc", "9.9.9").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(ContractError::MigrationDowngrade { from: "9.9.9".to_string(), to: env!("CARGO_PKG_VERSION").to_string() }, err);
+
+        set_contract_version(&mut deps.storage, "crates.io:cw721-nfc", "latest").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(ContractError::InvalidContractVersion { version: "latest".to_string() }, err);
+
+        set_contract_version(&mut deps.storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(ContractError::InvalidContractName { contract: "crates.io:cw20-base".to_string() }, err);
+    }
+
+    #[test]
+    fn paging_through_physicals() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let info = mock_info("creator", &[]);
+        let msg = UpdateTierInfo { tier: 2, max_physical_limit: 50, cost: 120 * 1_000_000};
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // token 1 is passed around 35 owners, each of them orders a tier 2 print
+        deps.querier.set_cw721_token("owner1", 1);
+        for i in 1..36 {
+            let owner = format!("owner{}", i);
+            deps.querier.transfer_cw721_token(&owner, 1);
+            let info = mock_info(&owner, &[coin(130 * 1_000_000, "uusd")]);
+            let msg = OrderCw721Print { token_id: 1.to_string(), tier: 2.to_string(), recipient: None };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        let all_ids: Vec<String> = (1..36).map(|id: u32| id.to_string()).collect();
+
+        // limit is capped to MAX_LIMIT
+        let msg = QueryMsg::AllCw721Physicals { start_after: None, limit: Some(100), order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!(all_ids[..30].to_vec(), page.physicals);
+        assert_eq!(Some(30), page.next_cursor);
+        let msg = QueryMsg::AllCw721Physicals { start_after: page.next_cursor, limit: Some(100), order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!(all_ids[30..].to_vec(), page.physicals);
+        assert_eq!(None, page.next_cursor);
+
+        // descending order
+        let msg = QueryMsg::AllCw721Physicals { start_after: None, limit: Some(100), order_by: Some(OrderBy::Descending) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!("35", page.physicals[0]);
+        assert_eq!(Some(6), page.next_cursor);
+        let msg = QueryMsg::AllCw721Physicals { start_after: page.next_cursor, limit: Some(100), order_by: Some(OrderBy::Descending) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: AllPhysicalsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["5", "4", "3", "2", "1"], page.physicals);
+        assert_eq!(None, page.next_cursor);
+
+        // paging by token id with small pages
+        let mut ids: Vec<String> = vec![];
+        let mut start_after = None;
+        loop {
+            let msg = QueryMsg::Cw721Physicals { token_id: "1".to_string(), start_after, limit: Some(7), order_by: None };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let page: Cw721PhysicalsResponse = from_binary(&res).unwrap();
+            ids.extend(page.physicals);
+            start_after = page.next_cursor;
+            if start_after.is_none() {
+                break;
+            }
+        }
+        assert_eq!(all_ids, ids);
+
+        // full records page the same way
+        let msg = QueryMsg::Cw721PhysicalsInfo { token_id: "1".to_string(), tier: Some(2), status: None, start_after: Some(30), limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![31, 32, 33, 34, 35], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(None, page.next_cursor);
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: None, status: None, start_after: Some(30), limit: Some(2), order_by: Some(OrderBy::Descending) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![29, 28], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(Some(28), page.next_cursor);
+    }
+
+    #[test]
+    fn query_physicals_by_owner() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("bob", 2);
+        deps.querier.set_cw721_token("alice", 3);
+
+        let orders = [("alice", "1", "3", 10), ("bob", "2", "3", 10), ("alice", "3", "2", 130), ("alice", "1", "2", 130)];
+        for (sender, token_id, tier, ust) in orders.iter() {
+            let info = mock_info(sender, &[coin(ust * 1_000_000, "uusd")]);
+            let msg = OrderCw721Print { token_id: token_id.to_string(), tier: tier.to_string(), recipient: None };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // alice's prints
+        let msg = QueryMsg::PhysicalsByOwner { owner: "alice".to_string(), start_after: None, limit: Some(2) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 3], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(Some(3), page.next_cursor);
+        let msg = QueryMsg::PhysicalsByOwner { owner: "alice".to_string(), start_after: page.next_cursor, limit: Some(2) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![4], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(None, page.next_cursor);
+
+        // index follows owner changes of the physical item
+        deps.querier.transfer_cw721_token("bob", 1);
+        let info = mock_info("random", &[]);
+        execute(deps.as_mut(), mock_env(), info, SyncPhysicalOwner { physical_id: 1 }).unwrap();
+        let msg = QueryMsg::PhysicalsByOwner { owner: "bob".to_string(), start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        let msg = QueryMsg::PhysicalsByOwner { owner: "alice".to_string(), start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3, 4], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn query_physicals_by_status_and_tier() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        for token_id in 1..5 {
+            deps.querier.set_cw721_token("alice", token_id);
+            for (tier, ust) in [(2, 130), (3, 10)].iter() {
+                let info = mock_info("alice", &[coin(ust * 1_000_000, "uusd")]);
+                let msg = OrderCw721Print { token_id: token_id.to_string(), tier: tier.to_string(), recipient: None };
+                execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            }
+        }
+        // tier 2 prints are 1, 3, 5, 7, first one goes into production
+        let info = mock_info("creator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::InProduction };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::PhysicalsByStatus { status: PhysicalStatus::Pending, tier: 2, start_after: None, limit: Some(2) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3, 5], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(Some(5), page.next_cursor);
+        let msg = QueryMsg::PhysicalsByStatus { status: PhysicalStatus::Pending, tier: 2, start_after: page.next_cursor, limit: Some(2) };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![7], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(None, page.next_cursor);
+
+        let msg = QueryMsg::PhysicalsByStatus { status: PhysicalStatus::InProduction, tier: 2, start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1], page.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+
+        let msg = QueryMsg::PhysicalsByStatus { status: PhysicalStatus::InProduction, tier: 3, start_after: None, limit: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let page: PhysicalsByStatusResponse = from_binary(&res).unwrap();
+        assert_eq!(0, page.physicals.len());
+    }
+
+    #[test]
+    fn keeping_stats_consistent() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap();
+        let stats: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, stats.stats.tiers.len());
+        assert!(stats.stats.statuses.iter().all(|s| s.physicals == 0));
+        assert_eq!(0, stats.stats.unique_orderers);
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("alice", 2);
+        deps.querier.set_cw721_token("bob", 3);
+
+        // alice orders two tier 2 prints, bob orders a tier 3 print
+        let orders = [("alice", "1", "2", 130), ("alice", "2", "2", 130), ("bob", "3", "3", 10)];
+        for (sender, token_id, tier, ust) in orders.iter() {
+            let info = mock_info(sender, &[coin(ust * 1_000_000, "uusd")]);
+            let msg = OrderCw721Print { token_id: token_id.to_string(), tier: tier.to_string(), recipient: None };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // alice's first print gets cancelled
+        let info = mock_info("creator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::Cancelled };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // bob wins the masterpiece auction
+        let mut env = mock_env();
+        let info = mock_info("bob", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        env.block.height += BIDDING_DURATION;
+        execute(deps.as_mut(), env.clone(), info.clone(), ResolveBids {}).unwrap();
+
+        // next round closes without any bid
+        env.block.height += BIDDING_PAUSE + BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env, info, ResolveBids {}).unwrap();
+        let round = res.events.iter().find(|event| event.ty == "round_resolved").unwrap();
+        assert_eq!(Attribute::new("round", "2"), round.attributes[0]);
+        assert_eq!(Attribute::new("winning_bids", "0"), round.attributes[1]);
+
+        // cancelled print keeps counting in its tier along with its revenue,
+        // the cancelled status counter tells it apart
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap();
+        let stats: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![
+            TierStats { tier: 1, physicals: 1, revenue: Uint128::from(3_000_000_000u128) },
+            TierStats { tier: 2, physicals: 2, revenue: Uint128::from(260_000_000u128) },
+            TierStats { tier: 3, physicals: 1, revenue: Uint128::from(10_000_000u128) },
+        ], stats.stats.tiers);
+        assert_eq!(vec![
+            StatusStats { status: PhysicalStatus::Pending, physicals: 3 },
+            StatusStats { status: PhysicalStatus::InProduction, physicals: 0 },
+            StatusStats { status: PhysicalStatus::Shipped, physicals: 0 },
+            StatusStats { status: PhysicalStatus::Delivered, physicals: 0 },
+            StatusStats { status: PhysicalStatus::Cancelled, physicals: 1 },
+        ], stats.stats.statuses);
+        // every resolved round counts, the empty one doesn't change the highest winning bid
+        assert_eq!(2, stats.stats.auction_rounds);
+        assert_eq!(Uint128::from(3_000_000_000u128), stats.stats.highest_winning_bid);
+        assert_eq!(2, stats.stats.unique_orderers);
+
+        // status counters always add up to the number of physical items
+        let total: u32 = stats.stats.statuses.iter().map(|s| s.physicals).sum();
+        let physicals: u32 = stats.stats.tiers.iter().map(|t| t.physicals).sum();
+        assert_eq!(total, physicals);
+    }
+
+    #[test]
+    fn query_physicals_info_with_filters() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("bob", 2);
+
+        // alice orders tier 3 and tier 2, bob orders tier 3 physical items
+        let orders = [("alice", "1", "3", 10), ("alice", "1", "2", 130), ("bob", "2", "3", 10)];
+        for (sender, token_id, tier, ust) in orders.iter() {
+            let info = mock_info(sender, &[coin(ust * 1_000_000, "uusd")]);
+            let msg = OrderCw721Print { token_id: token_id.to_string(), tier: tier.to_string(), recipient: None };
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // first physical item goes into production later on
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(600);
+        let info = mock_info("creator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 1, status: PhysicalStatus::InProduction };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // full records of all physical items
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: None, status: None, start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(3, physicals.physicals.len());
+        assert_eq!(Cw721PhysicalInfo {
+            id: 1,
+            token_id: "1".to_string(),
+            owner: Addr::unchecked("alice"),
+            tier: 3,
+            status: PhysicalStatus::InProduction,
+            detached: false,
+            created_at: mock_env().block.time,
+            updated_at: env.block.time
+        }, physicals.physicals[0]);
+
+        // filter by tier
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: None, start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 3], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+
+        // filter by tier and status
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: Some(PhysicalStatus::Pending), start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+
+        // filters are applied before the limit, cursor continues after last returned id
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: None, start_after: None, limit: Some(1), order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: None, start_after: Some(1), limit: Some(1), order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![3], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+
+        // filtered pages scan a bounded number of items and continue after the last scanned id
+        for id in 4..=304u32 {
+            let physical = Cw721PhysicalInfo {
+                id,
+                token_id: "2".to_string(),
+                owner: Addr::unchecked("bob"),
+                tier: if id == 304 { 3 } else { 2 },
+                status: PhysicalStatus::Pending,
+                detached: false,
+                created_at: mock_env().block.time,
+                updated_at: mock_env().block.time
+            };
+            crate::state::physicals().save(&mut deps.storage, &U32Key::from(id).joined_key(), &physical).unwrap();
+        }
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: None, start_after: Some(3), limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert!(physicals.physicals.is_empty());
+        assert_eq!(Some(303), physicals.next_cursor);
+        let msg = QueryMsg::Cw721PhysicalsInfo { token_id: "2".to_string(), tier: Some(3), status: None, start_after: Some(303), limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![304], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        assert_eq!(None, physicals.next_cursor);
+        // tier and status together go through their index
+        let msg = QueryMsg::AllCw721PhysicalsInfo { tier: Some(3), status: Some(PhysicalStatus::Pending), start_after: Some(3), limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: AllPhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![304], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+
+        // physical items of token 1
+        let msg = QueryMsg::Cw721PhysicalsInfo { token_id: "1".to_string(), tier: None, status: None, start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        let msg = QueryMsg::Cw721PhysicalsInfo { token_id: "1".to_string(), tier: None, status: Some(PhysicalStatus::Pending), start_after: None, limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![2], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+        let msg = QueryMsg::Cw721PhysicalsInfo { token_id: "1".to_string(), tier: None, status: None, start_after: Some(1), limit: None, order_by: None };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![2], physicals.physicals.iter().map(|p| p.id).collect::<Vec<u32>>());
+    }
+
     #[test]
     fn overbidding_current_bids() {
         let mut deps = mock_dependencies();
@@ -483,7 +2024,7 @@ mod tests {
 
         // random cannot place bid
         let info = mock_info("chuck", &[coin(2510 * 1000000, "uusd")]);
-        let msg = Bid721Masterpiece { token_id: "1".to_string()};
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
         let err =
             execute(deps.as_mut(), mock_env(), info, msg.clone())
                 .unwrap_err();
@@ -491,8 +2032,8 @@ mod tests {
 
         // alice places first bid
         let alice_bid_funds = coin(2510 * 1000000, "uusd");
-        let info = mock_info("alice", &[alice_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 1.to_string()};
+        let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
             .unwrap();
         assert_eq!(0, res.messages.len());
@@ -503,22 +2044,23 @@ mod tests {
         assert_eq!(1, bids.bids.len());
         assert_eq!(vec![BidInfo{
             bid_amount: alice_bid_funds.amount,
-            owner: info.sender,
+            owner: info.sender.clone(),
+            bidder: info.sender,
             token_id: "1".to_string()
         }], bids.bids);
 
         // bob cannot place bid with same UST amount
         let bob_bid_funds = coin(2510 * 1000000, "uusd");
-        let info = mock_info("bob", &[bob_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 2.to_string()};
+        let info = mock_info("bob", std::slice::from_ref(&bob_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 2.to_string(), recipient: None };
         let err = execute(deps.as_mut(), mock_env(), info, msg.clone())
             .unwrap_err();
         assert_eq!(err, ContractError::LowBidding {});
 
         // bob can overbid alice
         let bob_bid_funds = coin(2600 * 1000000, "uusd");
-        let info = mock_info("bob", &[bob_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 2.to_string()};
+        let info = mock_info("bob", std::slice::from_ref(&bob_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 2.to_string(), recipient: None };
         let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone())
             .unwrap();
         // Check if message sending UST back to alice
@@ -539,11 +2081,326 @@ mod tests {
         assert_eq!(1, bids.bids.len());
         assert_eq!(vec![BidInfo{
             bid_amount: bob_bid_funds.amount,
-            owner: info.sender,
+            owner: info.sender.clone(),
+            bidder: info.sender,
             token_id: "2".to_string()
         }], bids.bids);
     }
 
+    #[test]
+    fn emitting_events() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("bob", 2);
+
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(vec![Event::new("order_placed")
+            .add_attribute("physical_id", "1")
+            .add_attribute("token_id", "1")
+            .add_attribute("tier", "3")
+            .add_attribute("owner", "alice")
+            .add_attribute("sender", "alice")
+            .add_attribute("amount", "10000000")], res.events);
+
+        let info = mock_info("alice", &[coin(2510 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(vec![Event::new("bid_placed")
+            .add_attribute("slot", "1")
+            .add_attribute("token_id", "1")
+            .add_attribute("owner", "alice")
+            .add_attribute("bidder", "alice")
+            .add_attribute("amount", "2510000000")], res.events);
+
+        let info = mock_info("bob", &[coin(2600 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "2".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(vec![
+            Event::new("bid_outbid")
+                .add_attribute("slot", "1")
+                .add_attribute("token_id", "1")
+                .add_attribute("owner", "alice")
+                .add_attribute("bidder", "alice")
+                .add_attribute("refunded_amount", "2510000000")
+                .add_attribute("outbid_by", "bob"),
+            Event::new("bid_placed")
+                .add_attribute("slot", "1")
+                .add_attribute("token_id", "2")
+                .add_attribute("owner", "bob")
+                .add_attribute("bidder", "bob")
+                .add_attribute("amount", "2600000000")
+        ], res.events);
+
+        env.block.height += BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        assert_eq!(vec![Event::new("round_resolved")
+            .add_attribute("round", "1")
+            .add_attribute("winning_bids", "1")
+            .add_attribute("physical_ids", "2")
+            .add_attribute("highest_bid", "2600000000")
+            .add_attribute("skipped_bids", "0")
+            .add_attribute("skipped_token_ids", "")
+            .add_attribute("promoted_bids", "0")
+            .add_attribute("next_start", (env.block.height + BIDDING_PAUSE).to_string())
+            .add_attribute("next_expires", Expiration::AtHeight(env.block.height + BIDDING_PAUSE + BIDDING_DURATION).to_string())
+        ], res.events);
+
+        let owner = mock_info("creator", &[]);
+        let msg = UpdatePhysicalStatus { physical_id: 2, status: PhysicalStatus::InProduction };
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        assert_eq!(vec![Event::new("physical_status_changed")
+            .add_attribute("physical_id", "2")
+            .add_attribute("token_id", "2")
+            .add_attribute("owner", "bob")
+            .add_attribute("previous_status", "PENDING")
+            .add_attribute("status", "IN_PRODUCTION")], res.events);
+
+        let msg = UpdateTierInfo { tier: 3, max_physical_limit: 100, cost: 10 * 1_000_000 };
+        let res = execute(deps.as_mut(), env, owner, msg).unwrap();
+        assert_eq!(vec![Event::new("tier_updated")
+            .add_attribute("tier", "3")
+            .add_attribute("max_physical_limit", "100")
+            .add_attribute("cost", "10000000")], res.events);
+    }
+
+    #[test]
+    fn minting_certificates() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("bob", 2);
+
+        let set_certificate = |certificate: &str| UpdateConfig {
+            cw721: None,
+            paused: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: Some(certificate.to_string()),
+            bids_limit: None,
+            bidding_duration: None,
+            bidding_pause: None,
+            promote_runner_ups: None,
+            bid_uniqueness: None
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), set_certificate("certificates")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let owner = mock_info("creator", &[]);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), set_certificate("certificates")).unwrap();
+        assert_eq!(Attribute::new("certificate", "certificates"), res.attributes[1]);
+
+        // ordered print gets its certificate minted to the owner of the physical item
+        let info = mock_info("alice", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "1".to_string(), tier: "3".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let mint_msg = CertificateExecuteMsg::Mint(MintMsg {
+            token_id: "1".to_string(),
+            owner: "alice".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                name: Some("Certificate of authenticity #1".to_string()),
+                description: Some("Tier 3 physical print of token 1".to_string()),
+                attributes: Some(vec![
+                    Trait { display_type: None, trait_type: "token_id".to_string(), value: "1".to_string() },
+                    Trait { display_type: None, trait_type: "tier".to_string(), value: "3".to_string() },
+                    Trait { display_type: None, trait_type: "physical_id".to_string(), value: "1".to_string() }
+                ]),
+                ..Metadata::default()
+            })
+        });
+        assert_eq!(vec![SubMsg {
+            id: 1,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "certificates".to_string(),
+                msg: to_binary(&mint_msg).unwrap(),
+                funds: vec![]
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Always
+        }], res.messages);
+
+        // failed mint is reported without recording a certificate
+        let failed_reply = Reply { id: 1, result: ContractResult::Err("certificate contract failed".to_string()) };
+        let res = reply(deps.as_mut(), env.clone(), failed_reply).unwrap();
+        assert_eq!(vec![Event::new("certificate_failed")
+            .add_attribute("physical_id", "1")
+            .add_attribute("token_id", "1")
+            .add_attribute("owner", "alice")
+            .add_attribute("certificate_contract", "certificates")
+            .add_attribute("error", "certificate contract failed")], res.events);
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Certificate { physical_id: 1 }).unwrap();
+        let certificate: CertificateResponse = from_binary(&res).unwrap();
+        assert_eq!(None, certificate.certificate);
+
+        // certificate is recorded once the mint succeeds
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Certificate { physical_id: 1 }).unwrap();
+        let certificate: CertificateResponse = from_binary(&res).unwrap();
+        assert_eq!(None, certificate.certificate);
+        let mint_reply = |id: u64| Reply {
+            id,
+            result: ContractResult::Ok(SubMsgExecutionResponse { events: vec![], data: None })
+        };
+        let err = reply(deps.as_mut(), env.clone(), mint_reply(99)).unwrap_err();
+        assert_eq!(err, ContractError::UnknownReplyId { id: 99 });
+        reply(deps.as_mut(), env.clone(), mint_reply(1)).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Certificate { physical_id: 1 }).unwrap();
+        let certificate: CertificateResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(Certificate {
+            contract: Addr::unchecked("certificates"),
+            token_id: "1".to_string()
+        }), certificate.certificate);
+
+        // won masterpieces get certificates too
+        let info = mock_info("bob", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "2".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        env.block.height += BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(2, res.messages[0].id);
+
+        // no certificates are minted once the certificate contract is unset
+        execute(deps.as_mut(), env.clone(), owner, set_certificate("")).unwrap();
+        let info = mock_info("bob", &[coin(10 * 1_000_000, "uusd")]);
+        let msg = OrderCw721Print { token_id: "2".to_string(), tier: "3".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn skipping_bids_of_transferred_tokens() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+        let round_attribute = |res: &cosmwasm_std::Response, key: &str| res.events.iter()
+            .find(|event| event.ty == "round_resolved").unwrap()
+            .attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+
+        // winning bid of a sold token is refunded instead of creating the physical item
+        deps.querier.set_cw721_token("alice", 1);
+        let info = mock_info("alice", &[coin(3000 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "1".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        deps.querier.transfer_cw721_token("carol", 1);
+        env.block.height += BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        assert_eq!(vec![SubMsg::new(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(3000 * 1_000_000, "uusd")
+        })], res.messages);
+        assert_eq!("0", round_attribute(&res, "winning_bids"));
+        assert_eq!("1", round_attribute(&res, "skipped_bids"));
+        assert_eq!("1", round_attribute(&res, "skipped_token_ids"));
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap();
+        let stats: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(0, stats.stats.tiers[0].physicals);
+
+        // with promotion enabled outbid bids are held and replace the skipped ones
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: None, claim_policy: None, certificate: None, bids_limit: None, bidding_duration: None, bidding_pause: None, promote_runner_ups: Some(true), bid_uniqueness: None };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        env.block.height += BIDDING_PAUSE;
+        deps.querier.set_cw721_token("dave", 3);
+        deps.querier.set_cw721_token("erin", 4);
+        let info = mock_info("dave", &[coin(2600 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "3".to_string(), recipient: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let info = mock_info("erin", &[coin(2700 * 1_000_000, "uusd")]);
+        let msg = Bid721Masterpiece { token_id: "4".to_string(), recipient: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // held runner-up can't be withdrawn
+        deps.querier.set_balance(env.contract.address.as_str(), coins(5300 * 1_000_000, "uusd"));
+        let msg = WithdrawFunds { amount: Uint128::new(1), recipient: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds { available: 0, requested: 1 });
+
+        deps.querier.transfer_cw721_token("frank", 4);
+        env.block.height += BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        assert_eq!(vec![SubMsg::new(BankMsg::Send {
+            to_address: "erin".to_string(),
+            amount: coins(2700 * 1_000_000, "uusd")
+        })], res.messages);
+        assert_eq!("1", round_attribute(&res, "winning_bids"));
+        assert_eq!("4", round_attribute(&res, "skipped_token_ids"));
+        assert_eq!("1", round_attribute(&res, "promoted_bids"));
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GetCw721PhysicalInfo { token_id: "1".to_string() }).unwrap();
+        let physical: Cw721PhysicalInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("dave"), physical.physical.owner);
+        assert_eq!("3", physical.physical.token_id);
+
+        // runner-ups are limited to the bids limit, lower ones are refunded right away
+        env.block.height += BIDDING_PAUSE;
+        let bidders = [("gina", 5, 2600), ("hank", 6, 2700), ("ivan", 7, 2800)];
+        let mut responses = vec![];
+        for (bidder, token_id, amount) in bidders.iter() {
+            deps.querier.set_cw721_token(bidder, *token_id);
+            let info = mock_info(bidder, &[coin(amount * 1_000_000, "uusd")]);
+            let msg = Bid721Masterpiece { token_id: token_id.to_string(), recipient: None };
+            responses.push(execute(deps.as_mut(), env.clone(), info, msg).unwrap());
+        }
+        assert_eq!(0, responses[1].messages.len());
+        assert_eq!(vec![SubMsg::new(BankMsg::Send {
+            to_address: "gina".to_string(),
+            amount: coins(2600 * 1_000_000, "uusd")
+        })], responses[2].messages);
+        let dropped = responses[2].events.iter().find(|event| event.ty == "bid_invalidated").unwrap();
+        assert!(dropped.attributes.contains(&Attribute::new("reason", "runner_up_dropped")));
+        deps.querier.set_balance(env.contract.address.as_str(), coins(5500 * 1_000_000, "uusd"));
+        let msg = WithdrawFunds { amount: Uint128::new(1), recipient: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds { available: 0, requested: 1 });
+    }
+
+    #[test]
+    fn skipping_winning_bids_without_available_physicals() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let mut env = mock_env();
+        env.block.height = 12_344;
+        let msg = UpdateConfig { cw721: None, paused: None, escrow_orders: None, claim_policy: None, certificate: None, bids_limit: Some(2), bidding_duration: None, bidding_pause: None, promote_runner_ups: Some(true), bid_uniqueness: None };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        // both live bids target token 1, dave's outbid bid is held as a runner-up
+        env.block.height = 12_346;
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("dave", 4);
+        for (bidder, token_id, amount) in [("dave", 4, 2550), ("alice", 1, 2600), ("alice", 1, 2700)].iter() {
+            let info = mock_info(bidder, &[coin(amount * 1_000_000, "uusd")]);
+            let msg = Bid721Masterpiece { token_id: token_id.to_string(), recipient: None };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+
+        // the second winner of token 1 is refunded and the runner-up takes its slot
+        env.block.height += BIDDING_DURATION;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random", &[]), ResolveBids {}).unwrap();
+        assert_eq!(vec![SubMsg::new(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(2600 * 1_000_000, "uusd")
+        })], res.messages);
+        let invalidated = res.events.iter().find(|event| event.ty == "bid_invalidated").unwrap();
+        assert!(invalidated.attributes.contains(&Attribute::new("slot", "2")));
+        assert!(invalidated.attributes.contains(&Attribute::new("reason", "not_available")));
+        let resolved = res.events.iter().find(|event| event.ty == "round_resolved").unwrap();
+        assert!(resolved.attributes.contains(&Attribute::new("winning_bids", "2")));
+        assert!(resolved.attributes.contains(&Attribute::new("skipped_token_ids", "1")));
+        assert!(resolved.attributes.contains(&Attribute::new("promoted_bids", "1")));
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Cw721PhysicalsInfo { token_id: "1".to_string(), tier: None, status: None, start_after: None, limit: None, order_by: None }).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(1, physicals.physicals.len());
+        let res = query(deps.as_ref(), env, QueryMsg::Cw721PhysicalsInfo { token_id: "4".to_string(), tier: None, status: None, start_after: None, limit: None, order_by: None }).unwrap();
+        let physicals: Cw721PhysicalsInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(Addr::unchecked("dave"), physicals.physicals[0].owner);
+    }
+
     #[test]
     fn bidding_allowed_only_inside_bidding_window() {
         let mut deps = mock_dependencies();
@@ -553,8 +2410,8 @@ mod tests {
 
         // alice cannot place bid before bidding window starts
         let mut alice_bid_funds = coin(5000 * 1_000_000, "uusd");
-        let info = mock_info("alice", &[alice_bid_funds.clone()]);
-        let msg = Bid721Masterpiece { token_id: 1.to_string()};
+        let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
+        let msg = Bid721Masterpiece { token_id: 1.to_string(), recipient: None };
         let mut env = mock_env();
         env.block.height = 12_344;
         let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
@@ -564,20 +2421,21 @@ mod tests {
         loop {
             alice_bid_funds.amount = Uint128::from(alice_bid_funds.amount.u128() + 1_000_000);
             env.block.height += 1;
-            let info = mock_info("alice", &[alice_bid_funds.clone()]);
+            let info = mock_info("alice", std::slice::from_ref(&alice_bid_funds));
             let result = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
             match result {
                 Err(err) => {
                     // Bidding windows expired at 12345 + 19440
                     assert_eq!(err, ContractError::BiddingNotAllowed {});
-                    assert_eq!(123_45 + BIDDING_DURATION, env.block.height);
+                    assert_eq!(12_345 + BIDDING_DURATION, env.block.height);
                     // Check last alice's bid
                     let res = query(deps.as_ref(),mock_env(), QueryMsg::Bids {}).unwrap();
                     let bids: BidsResponse = from_binary(&res).unwrap();
                     assert_eq!(1, bids.bids.len());
                     assert_eq!(vec![BidInfo{
                         bid_amount: Uint128::from(alice_bid_funds.amount.u128() - 1_000_000),
-                        owner: info.sender,
+                        owner: info.sender.clone(),
+                        bidder: info.sender,
                         token_id: "1".to_string()
                     }], bids.bids);
                     break
@@ -586,4 +2444,88 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn enforcing_bid_uniqueness() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        deps.querier.set_cw721_token("alice", 1);
+        deps.querier.set_cw721_token("alice", 2);
+        deps.querier.set_cw721_token("bob", 3);
+
+        let update_msg = |bid_uniqueness: BidUniqueness, bids_limit: Option<u8>| UpdateConfig {
+            cw721: None,
+            paused: None,
+            escrow_orders: None,
+            claim_policy: None,
+            certificate: None,
+            bids_limit,
+            bidding_duration: None,
+            bidding_pause: None,
+            promote_runner_ups: None,
+            bid_uniqueness: Some(bid_uniqueness)
+        };
+        let uniqueness = BidUniqueness { per_token: true, per_bidder: true };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("random", &[]), update_msg(uniqueness, None)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        // bids limit takes effect right away before the bidding window opens
+        let mut env = mock_env();
+        env.block.height = 12_344;
+        execute(deps.as_mut(), env, mock_info("creator", &[]), update_msg(uniqueness, Some(3))).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 12_346;
+        let bid = |bidder: &str, token_id: u32, amount: u128| (
+            mock_info(bidder, &[coin(amount * 1_000_000, "uusd")]),
+            Bid721Masterpiece { token_id: token_id.to_string(), recipient: None }
+        );
+
+        // a bidder holds a single live bid
+        let (info, msg) = bid("alice", 1, 2600);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let (info, msg) = bid("alice", 2, 2600);
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::BidAlreadyExists { slot: 1, token_id: "1".to_string(), bidder: "alice".to_string() });
+
+        // a token holds a single live bid, even when bidders may bid repeatedly
+        let uniqueness = BidUniqueness { per_token: true, per_bidder: false };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update_msg(uniqueness, None)).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("unique_bids_per_bidder", "false")));
+        let (info, msg) = bid("alice", 1, 3000);
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::BidAlreadyExists { slot: 1, token_id: "1".to_string(), bidder: "alice".to_string() });
+        let (info, msg) = bid("alice", 2, 2700);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let (info, msg) = bid("bob", 3, 2800);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // bid slots are looked up by bidder and by token
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByBidder { bidder: "alice".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2], slots.bids.iter().map(|slot| slot.slot).collect::<Vec<u8>>());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByToken { token_id: "3".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![BidSlot {
+            slot: 3,
+            bid: BidInfo {
+                bid_amount: Uint128::from(2800 * 1_000_000u128),
+                owner: Addr::unchecked("bob"),
+                bidder: Addr::unchecked("bob"),
+                token_id: "3".to_string()
+            }
+        }], slots.bids);
+
+        // without uniqueness the same token and bidder take over the lowest slot
+        let uniqueness = BidUniqueness { per_token: false, per_bidder: false };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update_msg(uniqueness, None)).unwrap();
+        let (info, msg) = bid("alice", 2, 3000);
+        execute(deps.as_mut(), env, info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByToken { token_id: "2".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert_eq!(vec![1, 2], slots.bids.iter().map(|slot| slot.slot).collect::<Vec<u8>>());
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::BidsByToken { token_id: "1".to_string() }).unwrap();
+        let slots: BidSlotsResponse = from_binary(&res).unwrap();
+        assert!(slots.bids.is_empty());
+    }
 }
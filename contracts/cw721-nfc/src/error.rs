@@ -17,6 +17,9 @@ pub enum ContractError {
     #[error("You already own this physical item")]
     AlreadyOwned {},
 
+    #[error("Physical item of this tier was already claimed with this token")]
+    TierAlreadyClaimed {},
+
     #[error("You need to provide correct tier parameter")]
     InvalidTier {},
 
@@ -38,12 +41,109 @@ pub enum ContractError {
     #[error("Only UST among native tokens accepted")]
     OnlyUSTAccepted {},
 
+    #[error("No funds sent")]
+    NoFunds {},
+
+    #[error("Invalid token id {token_id:?}")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Failed to query the owner of token {token_id} from cw721 contract: {reason}")]
+    Cw721QueryFailed { token_id: String, reason: String },
+
+    #[error("Invalid physical item id {physical_id:?}")]
+    InvalidPhysicalId { physical_id: String },
+
     #[error("Tier max Limit can't be set to zero")]
     TierMaxLimitIsZero {},
 
+    #[error("Bids limit can't be set to zero")]
+    BidsLimitIsZero {},
+
+    #[error("Bidding duration can't be set to zero")]
+    BiddingDurationIsZero {},
+
+    #[error("Bidding window with placed bids can't be rescheduled")]
+    BiddingHasBids {},
+
+    #[error("Bidding window can't start in the past")]
+    InvalidBiddingStart {},
+
+    #[error("Bidding window can't end beyond the last representable block height or time")]
+    InvalidBiddingSchedule {},
+
+    #[error("Switching the bidding mode requires both durations in units of the new mode")]
+    BiddingDurationsRequired {},
+
+    #[error("Cw721 contract can't be changed while tokens are escrowed")]
+    Cw721InUse {},
+
+    #[error("Config update needs at least one field to change")]
+    EmptyConfigUpdate {},
+
     #[error("You need to over-bid previous bidder")]
     LowBidding {},
 
+    #[error("Bid in slot {slot} by {bidder} for token {token_id} is still live")]
+    BidAlreadyExists { slot: u8, token_id: String, bidder: String },
+
     #[error("Unauthorized")]
     BiddingNotAllowed {},
+
+    #[error("Recipient needs to be set to the token owner when acting on owner's behalf")]
+    RecipientRequired {},
+
+    #[error("Recipient is not the owner of the token")]
+    RecipientNotOwner {},
+
+    #[error("Token needs to be deposited to the contract before ordering")]
+    TokenNotEscrowed {},
+
+    #[error("Escrowing tokens is disabled")]
+    EscrowDisabled {},
+
+    #[error("Token can only be deposited by its owner")]
+    DepositNotByOwner {},
+
+    #[error("Token is locked until its physical items are delivered or cancelled")]
+    TokenLocked {},
+
+    #[error("Invalid physical item status")]
+    InvalidPhysicalStatus {},
+
+    #[error("Physical item is already delivered or cancelled")]
+    PhysicalItemClosed {},
+
+    #[error("Physical item was transferred by its holder and doesn't follow the token anymore")]
+    PhysicalItemDetached {},
+
+    #[error("Physical item {id} is not stored under its own id")]
+    PhysicalIdCollision { id: u32 },
+
+    #[error("Cannot migrate from contract {contract}")]
+    InvalidContractName { contract: String },
+
+    #[error("Invalid contract version {version}")]
+    InvalidContractVersion { version: String },
+
+    #[error("Cannot migrate from version {from} to older version {to}")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("There is no pending ownership proposal")]
+    NoOwnershipProposal {},
+
+    #[error("Insufficient funds, available {available} but requested {requested}")]
+    InsufficientFunds { available: u128, requested: u128 },
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Batch order needs at least one item")]
+    EmptyPrintOrder {},
+
+    #[error("Order item {index} (token_id {token_id}) failed: {error}")]
+    PrintOrderItemFailed {
+        index: u32,
+        token_id: String,
+        error: Box<ContractError>
+    },
 }
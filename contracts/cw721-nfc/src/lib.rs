@@ -2,6 +2,7 @@ extern crate core;
 
 pub mod contract;
 mod error;
+mod migrations;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Timestamp, Uint128};
+use cw2::{get_contract_version, ContractVersion};
+use cw_storage_plus::{Item, Map, PrimaryKey, U32Key, U8Key};
+
+use crate::error::ContractError;
+use crate::state::{bids, BidInfo, ClaimPolicy, ContractConfig, CONTRACT_CONFIG, PausableOperation, Cw721PhysicalInfo, PhysicalStatus, PHYSICALS_COUNT, physicals, physicals_count, Stats, STATS, ORDERERS, TokenClaim, TOKEN_CLAIMS};
+
+type Migration = fn(&mut dyn Storage) -> Result<(), ContractError>;
+
+/// Per-version migrations in ascending order, each one runs when the stored version is older
+const MIGRATIONS: [(&str, Migration); 3] = [
+    ("0.2.0", migrate_to_v0_2_0),
+    ("0.3.0", migrate_to_v0_3_0),
+    ("0.4.0", migrate_to_v0_4_0),
+];
+
+/// Config as stored up to 0.2.x, when the whole contract was paused at once
+#[derive(Serialize, Deserialize)]
+struct ConfigV0_2 {
+    owner: Addr,
+    cw721: Addr,
+    paused: bool,
+    #[serde(default)]
+    escrow_orders: bool,
+    #[serde(default)]
+    claim_policy: ClaimPolicy
+}
+
+/// Physical item as stored before the status became a typed enum
+#[derive(Serialize, Deserialize)]
+struct LegacyPhysicalInfo {
+    id: u32,
+    token_id: String,
+    owner: Addr,
+    tier: u8,
+    status: String,
+    #[serde(default)]
+    detached: bool,
+    #[serde(default)]
+    created_at: Timestamp,
+    #[serde(default)]
+    updated_at: Timestamp
+}
+
+/// Bid as stored before bids could be placed by delegates, the owner placed the bid
+#[derive(Serialize, Deserialize)]
+struct LegacyBidInfo {
+    bid_amount: Uint128,
+    token_id: String,
+    owner: Addr,
+    #[serde(default)]
+    bidder: Option<Addr>
+}
+
+const LEGACY_CONTRACT_NAME: &str = "crates.io:cw721-nfc";
+const LEGACY_VERSION: &str = "0.1.0";
+const LEGACY_CONFIG: Item<ConfigV0_2> = Item::new("contract_info");
+const CONFIG_V0_2: Item<ConfigV0_2> = Item::new("contract_config");
+const LEGACY_PHYSICALS: Map<&[u8], LegacyPhysicalInfo> = Map::new("physicals");
+const PHYSICALS_PRIMARY: Map<&[u8], Cw721PhysicalInfo> = Map::new("physicals");
+const LEGACY_BIDS: Map<U8Key, LegacyBidInfo> = Map::new("bids");
+const BIDS_PRIMARY: Map<U8Key, BidInfo> = Map::new("bids");
+
+/// ## Description
+/// Parses a `major.minor.patch` version with an optional `v` prefix,
+/// pre-release and build suffixes are ignored.
+pub fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let invalid = || ContractError::InvalidContractVersion { version: version.to_string() };
+    let core = version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    let parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u64>, ContractError>>()?;
+    match parts.as_slice() {
+        [major, minor, patch] => Ok((*major, *minor, *patch)),
+        _ => Err(invalid())
+    }
+}
+
+/// ## Description
+/// Loads the stored cw2 version. Contracts up to 0.1.x saved their config under the cw2 key,
+/// overwriting the version, so a config found there means the contract is on 0.1.0.
+/// Returns the stored version in [`Ok`], otherwise returns [`ContractError`]
+pub fn load_stored_version(storage: &dyn Storage) -> Result<ContractVersion, ContractError> {
+    match get_contract_version(storage) {
+        Ok(version) => Ok(version),
+        Err(err) => match LEGACY_CONFIG.may_load(storage) {
+            Ok(Some(_)) => Ok(ContractVersion {
+                contract: LEGACY_CONTRACT_NAME.to_string(),
+                version: LEGACY_VERSION.to_string()
+            }),
+            _ => Err(err.into())
+        }
+    }
+}
+
+/// ## Description
+/// Runs all migrations newer than the stored version, followed by the physical id check.
+/// Returns the last allocated physical id in [`Ok`], otherwise returns [`ContractError`]
+pub fn run_migrations(storage: &mut dyn Storage, stored_version: &str) -> Result<u32, ContractError> {
+    let stored = parse_version(stored_version)?;
+    for (version, migration) in MIGRATIONS.iter() {
+        if stored < parse_version(version)? {
+            migration(storage)?;
+        }
+    }
+    migrate_physical_ids(storage)
+}
+
+/// ## Description
+/// Upgrades the state of 0.1.x contracts:
+/// - moves the config out of the cw2 version key and stores the escrow and claim policy fields explicitly
+/// - converts physical item statuses from strings to [`PhysicalStatus`] and rebuilds
+///   the physical indexes added after the items were created
+/// - rebuilds the stats from the existing physical items, revenue of older orders is unknown
+/// - records the claims of physical items which aren't cancelled, claimed by their current owner
+/// - stores the bidder of live bids, which were placed by the token owner
+fn migrate_to_v0_2_0(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let config = match LEGACY_CONFIG.may_load(storage) {
+        Ok(Some(config)) => config,
+        _ => CONFIG_V0_2.load(storage)?
+    };
+    CONFIG_V0_2.save(storage, &config)?;
+
+    let legacy: Vec<(Vec<u8>, LegacyPhysicalInfo)> = LEGACY_PHYSICALS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let rebuild_stats = STATS.may_load(storage)?.is_none();
+    let mut stats = Stats::default();
+    for (key, item) in legacy {
+        if key != U32Key::from(item.id).joined_key() {
+            return Err(ContractError::PhysicalIdCollision { id: item.id });
+        }
+        let status = PhysicalStatus::from_legacy(&item.status)
+            .ok_or(ContractError::InvalidPhysicalStatus {})?;
+        let physical = Cw721PhysicalInfo {
+            id: item.id,
+            token_id: item.token_id,
+            owner: item.owner,
+            tier: item.tier,
+            status,
+            detached: item.detached,
+            created_at: item.created_at,
+            updated_at: item.updated_at
+        };
+        // store the typed record first, so that saving through the indexed map can load it
+        // and rewrites all of its index entries
+        PHYSICALS_PRIMARY.save(storage, &key, &physical)?;
+        physicals().save(storage, &key, &physical)?;
+        let claim_key = (physical.token_id.as_str(), U32Key::from(physical.id));
+        if physical.status != PhysicalStatus::Cancelled && !TOKEN_CLAIMS.has(storage, claim_key.clone()) {
+            TOKEN_CLAIMS.save(storage, claim_key, &TokenClaim {
+                physical_id: physical.id,
+                tier: physical.tier,
+                owner: physical.owner.clone()
+            })?;
+        }
+
+        if rebuild_stats {
+            stats.add_physical(physical.tier, physical.status, Default::default());
+            if !ORDERERS.has(storage, &physical.owner) {
+                ORDERERS.save(storage, &physical.owner, &true)?;
+                stats.unique_orderers += 1;
+            }
+        }
+    }
+    if rebuild_stats {
+        STATS.save(storage, &stats)?;
+    }
+
+    let legacy_bids: Vec<(Vec<u8>, LegacyBidInfo)> = LEGACY_BIDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for (key, bid) in legacy_bids {
+        let owner = bid.owner;
+        BIDS_PRIMARY.save(storage, U8Key::from(key[0]), &BidInfo {
+            bid_amount: bid.bid_amount,
+            token_id: bid.token_id,
+            bidder: bid.bidder.unwrap_or_else(|| owner.clone()),
+            owner
+        })?;
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Upgrades the state of 0.2.x contracts:
+/// - replaces the contract wide pause flag with the set of paused operations,
+///   a paused contract gets all of its operations paused
+fn migrate_to_v0_3_0(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let config = CONFIG_V0_2.load(storage)?;
+    CONTRACT_CONFIG.save(storage, &ContractConfig {
+        owner: config.owner,
+        cw721: config.cw721,
+        paused: if config.paused { PausableOperation::all() } else { vec![] },
+        escrow_orders: config.escrow_orders,
+        claim_policy: config.claim_policy,
+        certificate: None
+    })?;
+    Ok(())
+}
+
+/// ## Description
+/// Upgrades the state of 0.3.x contracts:
+/// - rebuilds the token and bidder indexes of the live bids, which were stored without them
+fn migrate_to_v0_4_0(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let live_bids: Vec<(Vec<u8>, BidInfo)> = BIDS_PRIMARY
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for (key, bid) in live_bids {
+        bids().save(storage, U8Key::from(key[0]), &bid)?;
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Verifies that every stored physical item lives under the key of its own id and moves the
+/// id allocator past the highest stored id, so that no allocated id can collide with an existing one.
+/// Returns the last allocated id in [`Ok`], otherwise returns [`ContractError`]
+fn migrate_physical_ids(storage: &mut dyn Storage) -> Result<u32, ContractError> {
+    let mut max_id = physicals_count(storage)?;
+    let stored: Vec<(Vec<u8>, Cw721PhysicalInfo)> = physicals()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for (key, physical) in stored.iter() {
+        if *key != U32Key::from(physical.id).joined_key() {
+            return Err(ContractError::PhysicalIdCollision { id: physical.id });
+        }
+        max_id = max_id.max(physical.id);
+    }
+    PHYSICALS_COUNT.save(storage, &max_id)?;
+    Ok(max_id)
+}
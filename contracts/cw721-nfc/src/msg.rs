@@ -1,27 +1,141 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Uint128};
 use cw0::Expiration;
+use cw721::Cw721ReceiveMsg;
+use cw721_metadata_onchain::TransferHookMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::state::{BidInfo, Cw721PhysicalInfo, TierInfo};
+use crate::state::{BidInfo, BidUniqueness, BiddingMode, Certificate, ClaimPolicy, Cw721PhysicalInfo, OwnershipChange, PausableOperation, PhysicalStatus, Role, Stats, TierInfo, TokenClaim};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub cw721: Addr,
+    pub cw721: String,
     pub tier_info: [TierInfo; 3],
     pub bids_limit: u8,
     pub bidding_duration: u64,
-    pub bidding_pause: u64
+    pub bidding_pause: u64,
+    /// Unit of the bidding durations, block heights by default
+    pub bidding_mode: Option<BiddingMode>,
+    pub escrow_orders: Option<bool>,
+    pub claim_policy: Option<ClaimPolicy>,
+    /// cw721-metadata-onchain contract minting a certificate of authenticity for every created
+    /// physical item, this contract needs to be its minter
+    pub certificate: Option<String>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    CreateCw721Physical{
-        token_id: u128,
-        nfc_tag: string
+    OrderCw721Print {
+        token_id: String,
+        tier: String,
+        recipient: Option<String>
+    },
+    OrderCw721Prints {
+        items: Vec<PrintOrderItem>
+    },
+    Bid721Masterpiece {
+        token_id: String,
+        recipient: Option<String>
+    },
+    ResolveBids {},
+    /// Deposits the NFT into escrow, sent by the cw721 contract on `SendNft`
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Moves the physical items along with the token and invalidates bids of the previous owner,
+    /// sent by the cw721 contract after every transfer when registered as its transfer hook.
+    /// Rejects deposits sent by an operator instead of the token owner
+    TransferHook(TransferHookMsg),
+    /// Returns an escrowed NFT which has no physical items in production
+    WithdrawNft {
+        token_id: String
+    },
+    UpdatePhysicalStatus {
+        physical_id: u32,
+        status: PhysicalStatus
+    },
+    /// Reconciles the owner of the physical item with the current owner of its token
+    SyncPhysicalOwner {
+        physical_id: u32
+    },
+    /// Transfers the physical item by its holder, it stops following the token afterwards
+    TransferPhysical {
+        physical_id: u32,
+        recipient: String
+    },
+    /// Proposes a new owner, the ownership is transferred once the proposed owner accepts it
+    ProposeNewOwner {
+        owner: String
+    },
+    DropOwnershipProposal {},
+    AcceptOwnership {},
+    AddRole {
+        role: Role,
+        address: String
+    },
+    RemoveRole {
+        role: Role,
+        address: String
+    },
+    /// Withdraws collected UST, funds of the active bids can't be withdrawn
+    WithdrawFunds {
+        amount: Uint128,
+        recipient: Option<String>
+    },
+    /// Moves the start of the next bidding window, a window without bids can be started right away.
+    /// Switching the bidding mode requires both durations in units of the new mode.
+    ScheduleBidding {
+        start: u64,
+        mode: Option<BiddingMode>,
+        duration: Option<u64>,
+        pause_duration: Option<u64>
+    },
+    /// Ends the current bidding window refunding all bids, the next window starts at `next_start`
+    /// or after the bidding pause
+    AbortBidding {
+        next_start: Option<u64>
+    },
+    UpdateConfig {
+        /// Can't be changed while any token is escrowed
+        cw721: Option<String>,
+        /// Replaces the set of paused operations, an empty set resumes all of them
+        paused: Option<Vec<PausableOperation>>,
+        escrow_orders: Option<bool>,
+        claim_policy: Option<ClaimPolicy>,
+        /// Certificate contract, an empty address stops minting certificates
+        certificate: Option<String>,
+        /// Bids limit and durations are applied from the next bidding window on, a limit changed
+        /// during a live window is staged until then. Durations are in units of the current bidding mode
+        bids_limit: Option<u8>,
+        bidding_duration: Option<u64>,
+        bidding_pause: Option<u64>,
+        /// Holds up to bids limit of the highest outbid bids until resolution, they replace winning bids
+        /// whose owner doesn't hold the token anymore. Lower outbid bids are refunded right away
+        promote_runner_ups: Option<bool>,
+        /// Allows only one live bid per token and/or per bidder
+        bid_uniqueness: Option<BidUniqueness>
+    },
+    UpdateTierInfo {
+        tier: u8,
+        max_physical_limit: u8,
+        cost: u64
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrintOrderItem {
+    pub token_id: String,
+    pub tier: String,
+    pub recipient: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721HookMsg {
+    Deposit {}
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -31,20 +145,84 @@ pub enum QueryMsg {
     },
     Cw721Physicals {
         token_id: String,
-        start_after: Option<String>,
+        start_after: Option<u32>,
         limit: Option<u32>,
+        order_by: Option<OrderBy>,
     },
     AllCw721Physicals {
-        start_after: Option<String>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    /// Same as `Cw721Physicals`, but returns full physical items filtered by tier and status
+    /// A page scans a bounded number of items, so it may hold less than `limit` items
+    /// while `next_cursor` points to the last scanned one
+    Cw721PhysicalsInfo {
+        token_id: String,
+        tier: Option<u8>,
+        status: Option<PhysicalStatus>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    /// Same as `AllCw721Physicals`, but returns full physical items filtered by tier and status
+    /// A page scans a bounded number of items, so it may hold less than `limit` items
+    /// while `next_cursor` points to the last scanned one
+    AllCw721PhysicalsInfo {
+        tier: Option<u8>,
+        status: Option<PhysicalStatus>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    PhysicalsByOwner {
+        owner: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    PhysicalsByStatus {
+        status: PhysicalStatus,
+        tier: u8,
+        start_after: Option<u32>,
         limit: Option<u32>,
     },
+    Config {},
+    Stats {},
+    Roles {},
     Bids {},
+    /// Live bids placed for the token
+    BidsByToken {
+        token_id: String
+    },
+    /// Live bids placed by the bidder
+    BidsByBidder {
+        bidder: String
+    },
     BiddingInfo {},
     TierInfo {
         tier: u8
+    },
+    EscrowedToken {
+        token_id: String
+    },
+    TokenClaims {
+        token_id: String
+    },
+    PhysicalOwnershipHistory {
+        physical_id: u32
+    },
+    Certificate {
+        physical_id: u32
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderBy {
+    Ascending,
+    Descending
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Cw721AddressResponse {
     pub cw721: Addr,
@@ -58,11 +236,71 @@ pub struct Cw721PhysicalInfoResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Cw721PhysicalsResponse {
     pub physicals: Vec<String>,
+    pub next_cursor: Option<u32>, // last returned id if there may be more physical items
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AllPhysicalsResponse {
     pub physicals: Vec<String>,
+    pub next_cursor: Option<u32>, // last returned id if there may be more physical items
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw721PhysicalsInfoResponse {
+    pub physicals: Vec<Cw721PhysicalInfo>,
+    pub next_cursor: Option<u32>, // last returned or scanned id if there may be more physical items
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllPhysicalsInfoResponse {
+    pub physicals: Vec<Cw721PhysicalInfo>,
+    pub next_cursor: Option<u32>, // last returned or scanned id if there may be more physical items
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhysicalsByOwnerResponse {
+    pub physicals: Vec<Cw721PhysicalInfo>,
+    pub next_cursor: Option<u32>, // last returned id if there may be more physical items
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhysicalsByStatusResponse {
+    pub physicals: Vec<Cw721PhysicalInfo>,
+    pub next_cursor: Option<u32>, // last returned id if there may be more physical items
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: Addr,
+    pub cw721: Addr,
+    pub paused: Vec<PausableOperation>,
+    pub escrow_orders: bool,
+    pub claim_policy: ClaimPolicy,
+    pub certificate: Option<Addr>,
+    pub bids_limit: u8,
+    pub bidding_duration: u64,
+    pub bidding_pause: u64,
+    pub bidding_mode: BiddingMode,
+    pub promote_runner_ups: bool,
+    pub bid_uniqueness: BidUniqueness,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembers {
+    pub role: Role,
+    pub members: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<RoleMembers>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub stats: Stats,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -76,13 +314,49 @@ pub struct BidsResponse {
     pub bids: Vec<BidInfo>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidSlot {
+    pub slot: u8,
+    pub bid: BidInfo
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidSlotsResponse {
+    pub bids: Vec<BidSlot>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowedTokenResponse {
+    pub owner: Option<Addr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenClaimsResponse {
+    pub claims: Vec<TokenClaim>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhysicalOwnershipHistoryResponse {
+    pub history: Vec<OwnershipChange>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CertificateResponse {
+    pub certificate: Option<Certificate>
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BiddingInfoResponse {
     pub bids_limit: u8,
     pub start: u64,
     pub expiration: Expiration,
     pub duration: u64,
-    pub pause_duration: u64
+    pub pause_duration: u64,
+    pub mode: BiddingMode,
+    pub promote_runner_ups: bool,
+    pub bid_uniqueness: BidUniqueness,
+    /// Bids limit of the next bidding window, staged during the live window
+    pub next_bids_limit: Option<u8>
 }
 
 
@@ -1,24 +1,173 @@
+use std::fmt;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage, Timestamp, Uint128};
 use cw0::Expiration;
-use cw_storage_plus::{Item, Map, IndexedMap, MultiIndex, IndexList, UniqueIndex, U32Key, Index, U8Key, U128Key};
+use cw_storage_plus::{Item, Map, IndexedMap, MultiIndex, IndexList, UniqueIndex, U32Key, Index, U8Key};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ContractConfig {
     pub owner: Addr,
     pub cw721: Addr,
-    pub paused: bool
+    pub paused: Vec<PausableOperation>,
+    #[serde(default)]
+    pub escrow_orders: bool, // ordering requires the NFT to be deposited to the contract
+    #[serde(default)]
+    pub claim_policy: ClaimPolicy,
+    #[serde(default)]
+    pub certificate: Option<Addr> // cw721-metadata-onchain contract minting certificates of created physical items
+}
+
+/// Operation which can be paused on its own
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableOperation {
+    /// Ordering prints of the tier
+    Ordering { tier: u8 },
+    Bidding,
+    Resolving,
+    /// Withdrawing escrowed tokens and collected funds
+    Withdrawals
+}
+
+impl PausableOperation {
+    pub fn all() -> Vec<PausableOperation> {
+        vec![
+            PausableOperation::Ordering { tier: 2 },
+            PausableOperation::Ordering { tier: 3 },
+            PausableOperation::Bidding,
+            PausableOperation::Resolving,
+            PausableOperation::Withdrawals
+        ]
+    }
+}
+
+impl fmt::Display for PausableOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PausableOperation::Ordering { tier } => write!(f, "ordering_tier_{}", tier),
+            PausableOperation::Bidding => write!(f, "bidding"),
+            PausableOperation::Resolving => write!(f, "resolving"),
+            PausableOperation::Withdrawals => write!(f, "withdrawals"),
+        }
+    }
+}
+
+/// How many physical items of the same tier can be claimed through a single token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimPolicy {
+    /// Every owner of the token can claim one physical item per tier
+    #[default]
+    PerOwner,
+    /// The token can claim one physical item per tier, regardless of its owner
+    PerToken
+}
+
+impl fmt::Display for ClaimPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaimPolicy::PerOwner => write!(f, "per_owner"),
+            ClaimPolicy::PerToken => write!(f, "per_token"),
+        }
+    }
+}
+
+/// Permissions which the owner can grant to other accounts, the owner implicitly has every role
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Updates the config and tier info
+    Admin,
+    /// Updates the fulfillment status of physical items
+    Operator,
+    /// Pauses and unpauses the contract
+    Pauser,
+    /// Withdraws the collected funds
+    Treasurer
+}
+
+pub const ROLES_LIST: [Role; 4] = [Role::Admin, Role::Operator, Role::Pauser, Role::Treasurer];
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::Operator => write!(f, "operator"),
+            Role::Pauser => write!(f, "pauser"),
+            Role::Treasurer => write!(f, "treasurer"),
+        }
+    }
+}
+
+/// Unit in which bidding windows are scheduled
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BiddingMode {
+    /// Start and durations are block heights and numbers of blocks
+    #[default]
+    Height,
+    /// Start and durations are unix timestamps and numbers of seconds
+    Time
+}
+
+impl fmt::Display for BiddingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BiddingMode::Height => write!(f, "height"),
+            BiddingMode::Time => write!(f, "time"),
+        }
+    }
+}
+
+impl BiddingMode {
+    /// Current block height or time in seconds
+    pub fn now(&self, block: &BlockInfo) -> u64 {
+        match self {
+            BiddingMode::Height => block.height,
+            BiddingMode::Time => block.time.seconds(),
+        }
+    }
+
+    pub fn expiration(&self, at: u64) -> Expiration {
+        match self {
+            BiddingMode::Height => Expiration::AtHeight(at),
+            BiddingMode::Time => Expiration::AtTime(Timestamp::from_seconds(at)),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BiddingInfo {
     pub bids_limit: u8,
-    pub duration: u64, // length of bidding window in blocks
+    pub duration: u64, // length of bidding window in blocks or seconds
     pub pause_duration: u64, // length of bidding pause between bidding windows
-    pub start: u64, // starting block height
-    pub expires: Expiration // end block height
+    pub start: u64, // starting block height or time
+    pub expires: Expiration, // end block height or time
+    #[serde(default)]
+    pub mode: BiddingMode,
+    #[serde(default)]
+    pub promote_runner_ups: bool, // outbid bids are held until resolution to replace invalidated winning bids
+    #[serde(default)]
+    pub bid_uniqueness: BidUniqueness,
+    #[serde(default)]
+    pub next_bids_limit: Option<u8> // bids limit changed during a live window, applied to the next one
+}
+
+/// Limits of the live bids, each enabled limit allows only one live bid per token or bidder.
+/// Both are disabled unless configured.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+pub struct BidUniqueness {
+    pub per_token: bool,
+    pub per_bidder: bool
+}
+
+impl BiddingInfo {
+    pub fn is_live(&self, block: &BlockInfo) -> bool {
+        self.mode.now(block) >= self.start && !self.expires.is_expired(block)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -27,10 +176,37 @@ pub struct Cw721PhysicalInfo {
     pub token_id: String,
     pub owner: Addr,
     pub tier: u8,
-    pub status: String
+    pub status: PhysicalStatus,
+    #[serde(default)]
+    pub detached: bool, // transferred by its holder, no longer follows the owner of the token
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub updated_at: Timestamp // last status or owner change
     // pub nfc_tag: Option<String>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipChange {
+    pub previous_owner: Addr,
+    pub owner: Addr,
+    pub block_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenClaim {
+    pub physical_id: u32,
+    pub tier: u8,
+    pub owner: Addr, // owner of the token when the physical item was claimed
+}
+
+/// Certificate of authenticity NFT minted for the physical item
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Certificate {
+    pub contract: Addr,
+    pub token_id: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TierInfo {
     pub max_physical_limit: u8,
@@ -41,24 +217,142 @@ pub struct TierInfo {
 pub struct BidInfo {
     pub bid_amount: Uint128,
     pub token_id: String,
-    pub owner: Addr,
+    pub owner: Addr, // token owner, receives the physical item
+    pub bidder: Addr, // sender of the bid, gets refunded when overbid
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PhysicalStatus {
+    Pending,
+    InProduction,
+    Shipped,
+    Delivered,
+    Cancelled
+}
+
+pub const PHYSICAL_STATUSES: [PhysicalStatus; 5] = [
+    PhysicalStatus::Pending,
+    PhysicalStatus::InProduction,
+    PhysicalStatus::Shipped,
+    PhysicalStatus::Delivered,
+    PhysicalStatus::Cancelled
+];
+
+impl PhysicalStatus {
+    /// Parses statuses stored as plain strings by older contract versions,
+    /// ignoring case and surrounding whitespace
+    pub fn from_legacy(status: &str) -> Option<PhysicalStatus> {
+        let status = status.trim().to_uppercase().replace([' ', '-'], "_");
+        PHYSICAL_STATUSES.iter().copied().find(|s| s.to_string() == status)
+    }
+}
+
+impl fmt::Display for PhysicalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicalStatus::Pending => write!(f, "PENDING"),
+            PhysicalStatus::InProduction => write!(f, "IN_PRODUCTION"),
+            PhysicalStatus::Shipped => write!(f, "SHIPPED"),
+            PhysicalStatus::Delivered => write!(f, "DELIVERED"),
+            PhysicalStatus::Cancelled => write!(f, "CANCELLED"),
+        }
+    }
+}
+
+/// Every physical item ordered or won in the tier and the UST paid for them. Cancelled physical
+/// items keep counting, cancelling doesn't refund the order, see the cancelled status counter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TierStats {
+    pub tier: u8,
+    pub physicals: u32,
+    pub revenue: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatusStats {
+    pub status: PhysicalStatus,
+    pub physicals: u32,
+}
+
+/// Aggregated statistics, updated whenever physical items are created or change status
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub tiers: Vec<TierStats>,
+    pub statuses: Vec<StatusStats>,
+    pub auction_rounds: u64, // resolved rounds, including the ones without winning bids
+    pub highest_winning_bid: Uint128,
+    pub unique_orderers: u64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            tiers: (1..=3)
+                .map(|tier| TierStats { tier, physicals: 0, revenue: Uint128::zero() })
+                .collect(),
+            statuses: PHYSICAL_STATUSES
+                .iter()
+                .map(|status| StatusStats { status: *status, physicals: 0 })
+                .collect(),
+            auction_rounds: 0,
+            highest_winning_bid: Uint128::zero(),
+            unique_orderers: 0,
+        }
+    }
+}
+
+impl Stats {
+    pub fn add_physical(&mut self, tier: u8, status: PhysicalStatus, paid: Uint128) {
+        match self.tiers.iter_mut().find(|stats| stats.tier == tier) {
+            Some(stats) => {
+                stats.physicals += 1;
+                stats.revenue += paid;
+            }
+            None => self.tiers.push(TierStats { tier, physicals: 1, revenue: paid }),
+        }
+        self.add_status(status);
+    }
+
+    pub fn change_status(&mut self, from: PhysicalStatus, to: PhysicalStatus) {
+        if let Some(stats) = self.statuses.iter_mut().find(|stats| stats.status == from) {
+            stats.physicals = stats.physicals.saturating_sub(1);
+        }
+        self.add_status(to);
+    }
+
+    fn add_status(&mut self, status: PhysicalStatus) {
+        match self.statuses.iter_mut().find(|stats| stats.status == status) {
+            Some(stats) => stats.physicals += 1,
+            None => self.statuses.push(StatusStats { status, physicals: 1 }),
+        }
+    }
+}
+
+impl Cw721PhysicalInfo {
+    /// Delivered or cancelled physical items can't change anymore
+    pub fn is_closed(&self) -> bool {
+        matches!(self.status, PhysicalStatus::Delivered | PhysicalStatus::Cancelled)
+    }
 }
 
 impl TierInfo {
     pub fn costs_sum(&self) -> u64 {
         let shipping_cost = 10 * 1_000_000;
-        return self.cost + shipping_cost;
+        self.cost + shipping_cost
     }
 }
 
 pub struct PhysicalIndexes<'a> {
     pub id: UniqueIndex<'a, U32Key, Cw721PhysicalInfo>,
     pub token_id: MultiIndex<'a, (String, Vec<u8>), Cw721PhysicalInfo>,
+    pub owner: MultiIndex<'a, (Addr, Vec<u8>), Cw721PhysicalInfo>,
+    pub status_tier: MultiIndex<'a, ((String, U8Key), Vec<u8>), Cw721PhysicalInfo>,
 }
 
 impl<'a> IndexList<Cw721PhysicalInfo> for PhysicalIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Cw721PhysicalInfo>> + '_> {
-        let v: Vec<&dyn Index<Cw721PhysicalInfo>> = vec![&self.id, &self.token_id];
+        let v: Vec<&dyn Index<Cw721PhysicalInfo>> = vec![&self.id, &self.token_id, &self.owner, &self.status_tier];
         Box::new(v.into_iter())
     }
 }
@@ -72,24 +366,109 @@ pub fn physicals<'a>() -> IndexedMap<'a, &'a [u8], Cw721PhysicalInfo, PhysicalIn
             |d, pk | (d.token_id.clone(), pk),
             "physicals",
             "physicals__token_id",
+        ),
+        owner: MultiIndex::new(
+            |d, pk | (d.owner.clone(), pk),
+            "physicals",
+            "physicals__owner",
+        ),
+        status_tier: MultiIndex::new(
+            |d, pk | ((d.status.to_string(), U8Key::from(d.tier)), pk),
+            "physicals",
+            "physicals__status_tier",
         )
     };
     IndexedMap::new("physicals", indexes)
 }
 
 
-pub const CONTRACT_CONFIG: Item<ContractConfig> = Item::new("contract_info");
+// "contract_info" is the cw2 version key, 0.1.x stored the config there
+pub const CONTRACT_CONFIG: Item<ContractConfig> = Item::new("contract_config");
+
+// proposed owner, becomes the owner once it accepts the ownership
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+// role -> accounts granted the role
+pub const ROLES: Map<&str, Vec<Addr>> = Map::new("roles");
+pub fn load_role_members(storage: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    Ok(ROLES.may_load(storage, &role.to_string())?.unwrap_or_default())
+}
 
 pub const TIERS: Map<U8Key, TierInfo> = Map::new("tiers");
 pub fn load_tier_info(storage: &dyn Storage, tier: u8, ) -> StdResult<TierInfo> {
     TIERS.load(storage, U8Key::from(tier))
 }
 
-pub const BIDS: Map<U8Key, BidInfo> = Map::new("bids");
+pub struct BidIndexes<'a> {
+    pub token_id: MultiIndex<'a, (String, Vec<u8>), BidInfo>,
+    pub bidder: MultiIndex<'a, (Addr, Vec<u8>), BidInfo>,
+}
+
+impl<'a> IndexList<BidInfo> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<BidInfo>> + '_> {
+        let v: Vec<&dyn Index<BidInfo>> = vec![&self.token_id, &self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+// bid slot -> live bid, indexed by token and bidder
+pub fn bids<'a>() -> IndexedMap<'a, U8Key, BidInfo, BidIndexes<'a>> {
+    let indexes = BidIndexes {
+        token_id: MultiIndex::new(
+            |d, pk | (d.token_id.clone(), pk),
+            "bids",
+            "bids__token_id",
+        ),
+        bidder: MultiIndex::new(
+            |d, pk | (d.bidder.clone(), pk),
+            "bids",
+            "bids__bidder",
+        )
+    };
+    IndexedMap::new("bids", indexes)
+}
+// highest outbid bids held until the bidding window is resolved, when runner-ups are promoted,
+// at most as many as the bids limit, lower ones are refunded right away
+pub const RUNNER_UP_BIDS: Item<Vec<BidInfo>> = Item::new("runner_up_bids");
+pub fn load_runner_up_bids(storage: &dyn Storage) -> StdResult<Vec<BidInfo>> {
+    Ok(RUNNER_UP_BIDS.may_load(storage)?.unwrap_or_default())
+}
 pub const BIDDING_INFO: Item<BiddingInfo> = Item::new("bidding_info");
 
-pub const PHYSICALS: Map<U128Key, Cw721PhysicalInfo> = Map::new("physicals");
+// last allocated physical id, ids are never reused
 pub const PHYSICALS_COUNT: Item<u32> = Item::new("physicals_count");
 
+pub fn physicals_count(storage: &dyn Storage) -> StdResult<u32> {
+    Ok(PHYSICALS_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
+/// ## Description
+/// Allocates the next physical id. Every physical item creation path must take its id from here.
+pub fn next_physical_id(storage: &mut dyn Storage) -> StdResult<u32> {
+    let id = physicals_count(storage)? + 1;
+    PHYSICALS_COUNT.save(storage, &id)?;
+    Ok(id)
+}
+
+// (token_id, physical_id) -> claim, cancelled physical items release their claim
+pub const TOKEN_CLAIMS: Map<(&str, U32Key), TokenClaim> = Map::new("token_claims");
+
+// physical_id -> ownership changes of the physical item, oldest first
+pub const PHYSICAL_OWNERS_HISTORY: Map<U32Key, Vec<OwnershipChange>> = Map::new("physical_owners_history");
+
+pub const STATS: Item<Stats> = Item::new("stats");
+// accounts which have ordered or won at least one physical item
+pub const ORDERERS: Map<&Addr, bool> = Map::new("orderers");
+
+pub fn load_stats(storage: &dyn Storage) -> StdResult<Stats> {
+    Ok(STATS.may_load(storage)?.unwrap_or_default())
+}
+
+// token_id -> account which deposited the NFT
+pub const ESCROWED_TOKENS: Map<&str, Addr> = Map::new("escrowed_tokens");
+
+// physical_id -> minted certificate, saved once the certificate contract confirms the mint
+pub const CERTIFICATES: Map<U32Key, Certificate> = Map::new("certificates");
+
 
 
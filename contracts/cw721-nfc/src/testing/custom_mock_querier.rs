@@ -1,6 +1,6 @@
 use cosmwasm_std::testing::MockQuerier;
 use cosmwasm_std::{
-    from_binary, from_slice, Empty, Querier, QuerierResult, QueryRequest, StdResult,
+    from_binary, from_slice, Coin, Empty, Querier, QuerierResult, QueryRequest, StdResult,
     SystemError, WasmQuery,
 };
 use cw721::Cw721QueryMsg;
@@ -41,7 +41,7 @@ impl CustomMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match request {
             QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr,
+                contract_addr: _,
                 msg,
             }) => {
                 // let contract_addr = Addr::unchecked(contract_addr);
@@ -57,6 +57,10 @@ impl CustomMockQuerier {
         }
     }
 
+    pub fn set_balance(&mut self, address: &str, balance: Vec<Coin>) {
+        self.base.update_balance(address, balance);
+    }
+
     pub fn set_cw721_token(&mut self, user: &str, token_id: u128) {
         self.cw721_querier.set_token_owner(user, token_id);
     }
@@ -65,4 +69,12 @@ impl CustomMockQuerier {
         self.cw721_querier.remove_token_owner(token_id);
         self.cw721_querier.set_token_owner(user, token_id);
     }
+
+    pub fn set_cw721_approval(&mut self, spender: &str, token_id: u128) {
+        self.cw721_querier.set_token_approval(spender, token_id);
+    }
+
+    pub fn set_cw721_operator(&mut self, owner: &str, operator: &str) {
+        self.cw721_querier.set_operator(owner, operator);
+    }
 }
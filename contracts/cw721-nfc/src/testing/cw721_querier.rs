@@ -1,10 +1,13 @@
 use cosmwasm_std::{to_binary, Addr, QuerierResult, SystemError};
-use cw721::{Cw721QueryMsg, OwnerOfResponse};
+use cw0::Expiration;
+use cw721::{Approval, ApprovedForAllResponse, Cw721QueryMsg, OwnerOfResponse};
 use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct Cw721Querier {
     token_owner: HashMap<String, Addr>,
+    token_approvals: HashMap<String, Vec<String>>,
+    operators: HashMap<String, Vec<String>>,
 }
 
 impl Cw721Querier {
@@ -12,7 +15,7 @@ impl Cw721Querier {
         match query {
             Cw721QueryMsg::OwnerOf {
                 token_id,
-                include_expired
+                include_expired: _
             } => {
                 let owner = match self.token_owner.get(&token_id) {
                     Some(balance) => balance,
@@ -25,14 +28,33 @@ impl Cw721Querier {
                     }
                 };
 
+                let approvals = self.token_approvals
+                    .get(&token_id)
+                    .map(|spenders| to_approvals(spenders, None))
+                    .unwrap_or_default();
+
                 Ok(to_binary(&OwnerOfResponse {
                     owner: Addr::to_string(owner),
-                    approvals: vec![]
+                    approvals
                 })
                     .into())
                     .into()
             }
 
+            Cw721QueryMsg::ApprovedForAll {
+                owner,
+                include_expired: _,
+                start_after,
+                limit: _
+            } => {
+                let operators = self.operators
+                    .get(&owner)
+                    .map(|operators| to_approvals(operators, start_after))
+                    .unwrap_or_default();
+
+                Ok(to_binary(&ApprovedForAllResponse { operators }).into()).into()
+            }
+
             query => Err(SystemError::InvalidRequest {
                 error: format!("[mock]: unsupported cw721 query {:?}", query),
                 request: Default::default(),
@@ -48,5 +70,33 @@ impl Cw721Querier {
 
     pub fn remove_token_owner(&mut self, token_id: u128) {
         self.token_owner.remove(token_id.to_string().as_str());
+        self.token_approvals.remove(token_id.to_string().as_str());
     }
+
+    pub fn set_token_approval(&mut self, spender: &str, token_id: u128) {
+        self.token_approvals
+            .entry(token_id.to_string())
+            .or_default()
+            .push(spender.to_string());
+    }
+
+    pub fn set_operator(&mut self, owner: &str, operator: &str) {
+        let operators = self.operators.entry(owner.to_string()).or_default();
+        operators.push(operator.to_string());
+        operators.sort();
+    }
+}
+
+fn to_approvals(spenders: &[String], start_after: Option<String>) -> Vec<Approval> {
+    spenders
+        .iter()
+        .filter(|spender| match &start_after {
+            Some(start) => *spender > start,
+            None => true
+        })
+        .map(|spender| Approval {
+            spender: spender.clone(),
+            expires: Expiration::Never {}
+        })
+        .collect()
 }
@@ -2,5 +2,4 @@ mod custom_mock_querier;
 mod helpers;
 mod cw721_querier;
 
-pub use custom_mock_querier::CustomMockQuerier;
 pub use helpers::mock_dependencies;